@@ -0,0 +1,127 @@
+use ark_ff::*;
+use ark_relations::r1cs::*;
+use ark_r1cs_std::prelude::*;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_std::*;
+
+use super::*;
+
+/// the bytes in little-endian order
+fn to_le_vars<ConstraintF: PrimeField>(
+    bytes: &[UInt8<ConstraintF>],
+    endianness: JZEndianness
+) -> Vec<UInt8<ConstraintF>> {
+    match endianness {
+        JZEndianness::Little => bytes.to_vec(),
+        JZEndianness::Big => bytes.iter().rev().cloned().collect(),
+    }
+}
+
+/// enforces that the bytes encode an integer below TargetF's modulus; the
+/// target may differ from the circuit's field, e.g. for record fields
+pub fn enforce_canonical<TargetF: PrimeField, ConstraintF: PrimeField>(
+    bytes: &[UInt8<ConstraintF>],
+    endianness: JZEndianness
+) -> Result<()> {
+    let mut bits = to_le_vars(bytes, endianness).to_bits_le()?;
+    // enforce_smaller_or_equal_than_le compares from the top bit of the
+    // bound downwards, so shorter inputs are padded up to its width
+    let num_bits = TargetF::MODULUS_BIT_SIZE as usize;
+    if bits.len() < num_bits {
+        bits.resize(num_bits, Boolean::FALSE);
+    }
+
+    let mut max = TargetF::MODULUS;
+    max.sub_with_borrow(&TargetF::BigInt::from(1u64));
+    Boolean::enforce_smaller_or_equal_than_le(&bits, max)?;
+
+    Ok(())
+}
+
+/// in-circuit bytes_to_field_checked: the bytes must encode an integer
+/// below the modulus, or the constraints are unsatisfied
+pub fn bytes_to_field_var_checked<ConstraintF: PrimeField>(
+    bytes: &[UInt8<ConstraintF>],
+    endianness: JZEndianness
+) -> Result<FpVar<ConstraintF>> {
+    let bits = to_le_vars(bytes, endianness).to_bits_le()?;
+    let num_bits = ConstraintF::MODULUS_BIT_SIZE as usize;
+
+    if bits.len() > num_bits {
+        Boolean::kary_or(&bits[num_bits..])?.enforce_equal(&Boolean::FALSE)?;
+    }
+
+    // le_bits_to_fp_var enforces the bound itself once there are
+    // MODULUS_BIT_SIZE bits; fewer bits are always below the modulus
+    Boolean::le_bits_to_fp_var(&bits[..cmp::min(bits.len(), num_bits)])
+}
+
+/// in-circuit field_to_bytes, over the unique bit decomposition of x
+pub fn field_var_to_bytes<ConstraintF: PrimeField>(
+    x: &FpVar<ConstraintF>,
+    endianness: JZEndianness
+) -> Result<Vec<UInt8<ConstraintF>>> {
+    let mut bits = x.to_bits_le()?;
+    bits.resize(8 * field_len::<ConstraintF>(), Boolean::FALSE);
+
+    let bytes: Vec<UInt8<ConstraintF>> = bits.chunks(8).map(UInt8::from_bits_le).collect();
+    Ok(to_le_vars(&bytes, endianness))
+}
+
+/// in-circuit bytes_to_limbs; limbs are below the modulus by construction,
+/// so no range checks are needed
+pub fn bytes_to_limb_vars<ConstraintF: PrimeField>(
+    bytes: &[UInt8<ConstraintF>],
+    endianness: JZEndianness
+) -> Result<Vec<FpVar<ConstraintF>>> {
+    to_le_vars(bytes, endianness)
+        .chunks(limb_len::<ConstraintF>())
+        .map(|limb| Boolean::le_bits_to_fp_var(&limb.to_bits_le()?))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_codec_constraints<F: PrimeField>() {
+        let modulus = F::MODULUS.to_bytes_le();
+        let x = F::from(0x0102_0304u64);
+        let wide: Vec<u8> = (0..100u8).map(|i| i.wrapping_mul(151)).collect();
+
+        for endianness in [JZEndianness::Little, JZEndianness::Big] {
+            let cs = ConstraintSystem::<F>::new_ref();
+
+            // canonical bytes decode to the native value and encode back
+            let bytes = field_to_bytes(&x, endianness);
+            let bytes_var = UInt8::new_witness_vec(cs.clone(), &bytes).unwrap();
+            let x_var = bytes_to_field_var_checked(&bytes_var, endianness).unwrap();
+            assert_eq!(x_var.value().unwrap(), x);
+            assert_eq!(field_var_to_bytes(&x_var, endianness).unwrap().value().unwrap(), bytes);
+
+            let wide_var = UInt8::new_witness_vec(cs.clone(), &wide).unwrap();
+            let limbs = bytes_to_limb_vars(&wide_var, endianness).unwrap();
+            assert_eq!(limbs.value().unwrap(), bytes_to_limbs::<F>(&wide, endianness));
+            assert!(cs.is_satisfied().unwrap());
+
+            // the modulus itself is rejected by both checks
+            let modulus_bytes = from_le(modulus.clone(), endianness);
+
+            let cs = ConstraintSystem::<F>::new_ref();
+            let modulus_var = UInt8::new_witness_vec(cs.clone(), &modulus_bytes).unwrap();
+            let _ = bytes_to_field_var_checked(&modulus_var, endianness).unwrap();
+            assert!(!cs.is_satisfied().unwrap(), "non-canonical field element accepted");
+
+            let cs = ConstraintSystem::<F>::new_ref();
+            let modulus_var = UInt8::new_witness_vec(cs.clone(), &modulus_bytes).unwrap();
+            enforce_canonical::<F, F>(&modulus_var, endianness).unwrap();
+            assert!(!cs.is_satisfied().unwrap(), "non-canonical bytes accepted");
+        }
+    }
+
+    #[test]
+    fn test_codec_constraints() {
+        check_codec_constraints::<ark_bls12_377::Fr>();
+        check_codec_constraints::<ark_bw6_761::Fr>();
+    }
+}
@@ -0,0 +1,161 @@
+pub mod constraints;
+
+use ark_ff::*;
+use ark_std::*;
+
+use crate::utils;
+
+/// byte order of an encoded integer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JZEndianness {
+    Little,
+    Big,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JZCodecError {
+    /// the bytes encode an integer at or above the field modulus
+    NonCanonical,
+    /// limb `limb` does not fit in limb_len bytes, or in the requested length
+    LimbOverflow { limb: usize },
+}
+
+/// bytes of a canonical encoding, e.g. 32 for BLS12-377 Fr and 48 for BW6-761 Fr
+pub fn field_len<F: PrimeField>() -> usize {
+    (F::MODULUS_BIT_SIZE as usize).div_ceil(8)
+}
+
+/// the most bytes that always fit below the modulus, e.g. 31 for BLS12-377 Fr
+pub fn limb_len<F: PrimeField>() -> usize {
+    (F::MODULUS_BIT_SIZE as usize - 1) / 8
+}
+
+/// the bytes in little-endian order
+pub fn to_le(bytes: &[u8], endianness: JZEndianness) -> Vec<u8> {
+    match endianness {
+        JZEndianness::Little => bytes.to_vec(),
+        JZEndianness::Big => bytes.iter().rev().cloned().collect(),
+    }
+}
+
+/// the bytes in the given order, from little-endian ones
+fn from_le(mut bytes: Vec<u8>, endianness: JZEndianness) -> Vec<u8> {
+    if endianness == JZEndianness::Big {
+        bytes.reverse();
+    }
+    bytes
+}
+
+/// reads the bytes as an integer and fails, instead of reducing, if it is
+/// not below F's modulus; inputs of any length are accepted as long as
+/// their value fits
+pub fn bytes_to_field_checked<F: PrimeField>(
+    bytes: &[u8],
+    endianness: JZEndianness
+) -> Result<F, JZCodecError> {
+    let bits = utils::bytes_to_bits(&to_le(bytes, endianness));
+    let num_bits = F::MODULUS_BIT_SIZE as usize;
+
+    if bits.iter().skip(num_bits).any(|&b| b) {
+        return Err(JZCodecError::NonCanonical);
+    }
+
+    let value = F::BigInt::from_bits_le(&bits[..cmp::min(bits.len(), num_bits)]);
+    F::from_bigint(value).ok_or(JZCodecError::NonCanonical)
+}
+
+pub fn is_canonical<F: PrimeField>(bytes: &[u8], endianness: JZEndianness) -> bool {
+    bytes_to_field_checked::<F>(bytes, endianness).is_ok()
+}
+
+/// the canonical field_len-byte encoding, the inverse of bytes_to_field_checked
+pub fn field_to_bytes<F: PrimeField>(x: &F, endianness: JZEndianness) -> Vec<u8> {
+    let mut bytes = x.into_bigint().to_bytes_le();
+    bytes.truncate(field_len::<F>());
+    from_le(bytes, endianness)
+}
+
+/// splits an integer of any size into limb_len-byte limbs, least
+/// significant limb first; every limb is canonical by construction
+pub fn bytes_to_limbs<F: PrimeField>(bytes: &[u8], endianness: JZEndianness) -> Vec<F> {
+    to_le(bytes, endianness)
+        .chunks(limb_len::<F>())
+        .map(F::from_le_bytes_mod_order)
+        .collect()
+}
+
+/// reassembles `len` bytes from bytes_to_limbs' output, failing if a limb
+/// does not fit in limb_len bytes or the value does not fit in `len` bytes
+pub fn limbs_to_bytes<F: PrimeField>(
+    limbs: &[F],
+    len: usize,
+    endianness: JZEndianness
+) -> Result<Vec<u8>, JZCodecError> {
+    let mut bytes = vec![];
+    for (i, limb) in limbs.iter().enumerate() {
+        let limb_bytes = field_to_bytes(limb, JZEndianness::Little);
+        if limb_bytes[limb_len::<F>()..].iter().any(|&b| b != 0) {
+            return Err(JZCodecError::LimbOverflow { limb: i });
+        }
+        bytes.extend_from_slice(&limb_bytes[..limb_len::<F>()]);
+    }
+
+    if bytes.len() > len {
+        if bytes[len..].iter().any(|&b| b != 0) {
+            return Err(JZCodecError::LimbOverflow { limb: len / limb_len::<F>() });
+        }
+        bytes.truncate(len);
+    }
+    bytes.resize(len, 0u8);
+
+    Ok(from_le(bytes, endianness))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_codec<F: PrimeField>() {
+        let modulus = F::MODULUS.to_bytes_le();
+        let mut max = F::MODULUS;
+        max.sub_with_borrow(&F::BigInt::from(1u64));
+
+        // p - 1 is the largest canonical encoding, p the smallest overflow
+        for endianness in [JZEndianness::Little, JZEndianness::Big] {
+            let max_bytes = from_le(max.to_bytes_le(), endianness);
+            assert_eq!(bytes_to_field_checked::<F>(&max_bytes, endianness), Ok(-F::one()));
+
+            let modulus_bytes = from_le(modulus.clone(), endianness);
+            assert_eq!(bytes_to_field_checked::<F>(&modulus_bytes, endianness), Err(JZCodecError::NonCanonical));
+            assert!(!is_canonical::<F>(&modulus_bytes, endianness));
+
+            let x = F::from(0x0102_0304u64);
+            let bytes = field_to_bytes(&x, endianness);
+            assert_eq!(bytes.len(), field_len::<F>());
+            assert_eq!(bytes_to_field_checked::<F>(&bytes, endianness), Ok(x));
+        }
+
+        // the byte order is the only difference between the two encodings
+        let x = F::from(0x0102u64);
+        assert_eq!(field_to_bytes(&x, JZEndianness::Little)[..2], [2, 1]);
+        assert_eq!(field_to_bytes(&x, JZEndianness::Big)[field_len::<F>() - 2..], [1, 2]);
+
+        // oversized inputs round-trip through limbs
+        let wide: Vec<u8> = (0..100u8).map(|i| i.wrapping_mul(151)).collect();
+        for endianness in [JZEndianness::Little, JZEndianness::Big] {
+            let limbs = bytes_to_limbs::<F>(&wide, endianness);
+            assert_eq!(limbs.len(), wide.len().div_ceil(limb_len::<F>()));
+            assert_eq!(limbs_to_bytes(&limbs, wide.len(), endianness), Ok(wide.clone()));
+        }
+
+        // a limb wider than limb_len bytes is rejected
+        let limbs = [F::one(), -F::one()];
+        assert_eq!(limbs_to_bytes(&limbs, 2 * limb_len::<F>(), JZEndianness::Little), Err(JZCodecError::LimbOverflow { limb: 1 }));
+    }
+
+    #[test]
+    fn test_codec() {
+        check_codec::<ark_bls12_377::Fr>();
+        check_codec::<ark_bw6_761::Fr>();
+    }
+}
@@ -0,0 +1,88 @@
+use ark_crypto_primitives::crh::{CRHSchemeGadget, pedersen};
+use ark_ec::twisted_edwards::{Projective, TECurveConfig};
+use ark_ff::PrimeField;
+use ark_r1cs_std::prelude::*;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::groups::curves::twisted_edwards::AffineVar;
+use ark_relations::r1cs::*;
+use ark_std::marker::PhantomData;
+
+use super::CRH;
+
+type PointVar<P> = AffineVar<P, FpVar<<P as ark_ec::CurveConfig>::BaseField>>;
+
+pub struct CRHGadget<P: TECurveConfig, W: pedersen::Window> {
+    _curve: PhantomData<P>,
+    _window: PhantomData<W>,
+}
+
+/// the input length is fixed at synthesis time, so the length prefix is a
+/// constant and only the chained blocks cost constraints
+impl<P: TECurveConfig, W: pedersen::Window> CRHSchemeGadget<CRH<P, W>, P::BaseField> for CRHGadget<P, W>
+    where   P::BaseField: PrimeField,
+{
+    type InputVar = [UInt8<P::BaseField>];
+    type OutputVar = PointVar<P>;
+    type ParametersVar = pedersen::constraints::CRHParametersVar<Projective<P>, PointVar<P>>;
+
+    fn evaluate(
+        parameters: &Self::ParametersVar,
+        input: &Self::InputVar
+    ) -> Result<Self::OutputVar> {
+        let block_len = CRH::<P, W>::block_len();
+
+        let mut length = (input.len() as u64).to_le_bytes().to_vec();
+        length.resize(CRH::<P, W>::state_len(), 0u8);
+        let mut state = UInt8::constant_vec(&length);
+
+        let mut output = PointVar::<P>::zero();
+        for i in 0..CRH::<P, W>::num_blocks(input.len()) {
+            let start = ark_std::cmp::min(i * block_len, input.len());
+            let end = ark_std::cmp::min(start + block_len, input.len());
+
+            let mut block = state.clone();
+            block.extend_from_slice(&input[start..end]);
+            output = pedersen::constraints::CRHGadget::<Projective<P>, PointVar<P>, W>::evaluate(
+                parameters,
+                &block
+            )?;
+
+            state = output.x.to_bytes()?;
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_crypto_primitives::crh::CRHScheme;
+    use ark_std::test_rng;
+    use crate::prf::config::ed_on_bw6_761::Window4x256;
+
+    type P = ark_ed_on_bw6_761::EdwardsConfig;
+    type H = CRH<P, Window4x256>;
+    type HG = CRHGadget<P, Window4x256>;
+    type ConstraintF = ark_bw6_761::Fr;
+
+    #[test]
+    fn test_chunked_pedersen_constraints() {
+        let mut rng = test_rng();
+        let params = H::setup(&mut rng).unwrap();
+
+        for len in [0, 80, 81, 200] {
+            let input: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            let digest = H::evaluate(&params, input.as_slice()).unwrap();
+
+            let cs = ConstraintSystem::<ConstraintF>::new_ref();
+            let params_var = <HG as CRHSchemeGadget<H, ConstraintF>>::ParametersVar::new_constant(
+                cs.clone(), &params
+            ).unwrap();
+            let input_var = UInt8::new_witness_vec(cs.clone(), &input).unwrap();
+
+            let digest_var = HG::evaluate(&params_var, &input_var).unwrap();
+            assert_eq!(digest_var.value().unwrap(), digest);
+            assert!(cs.is_satisfied().unwrap());
+        }
+    }
+}
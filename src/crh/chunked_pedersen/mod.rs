@@ -0,0 +1,113 @@
+pub mod constraints;
+
+use ark_crypto_primitives::crh::{CRHScheme, pedersen};
+use ark_crypto_primitives::Error;
+use ark_ec::twisted_edwards::{Affine, Projective, TECurveConfig};
+use ark_ff::PrimeField;
+use ark_serialize::CanonicalSerialize;
+use ark_std::{borrow::Borrow, marker::PhantomData, rand::Rng};
+
+/// Merkle–Damgård over the Pedersen CRH of window W, so inputs of any
+/// length hash with the same parameters:
+///
+///   s_0 = len(m) as u64 LE, padded to STATE_LEN bytes
+///   P_i = Pedersen(s_i || m_i),  s_{i+1} = x(P_i)
+///
+/// where m is split into blocks of W's capacity minus STATE_LEN bytes (an
+/// empty m is one empty block) and the output is the last P_i. Chaining on
+/// the x-coordinate keeps the state one field element wide, and the length
+/// prefix makes the zero padding of the last block unambiguous.
+pub struct CRH<P: TECurveConfig, W: pedersen::Window> {
+    _curve: PhantomData<P>,
+    _window: PhantomData<W>,
+}
+
+impl<P: TECurveConfig, W: pedersen::Window> CRH<P, W>
+    where   P::BaseField: PrimeField,
+{
+    /// bytes of the chaining value, i.e. of a serialized x-coordinate
+    pub fn state_len() -> usize {
+        P::BaseField::default().compressed_size()
+    }
+
+    /// message bytes per Pedersen block
+    pub fn block_len() -> usize {
+        let capacity = W::WINDOW_SIZE * W::NUM_WINDOWS / 8;
+        assert!(capacity > Self::state_len(), "window too small to chain");
+        capacity - Self::state_len()
+    }
+
+    pub fn num_blocks(len: usize) -> usize {
+        ark_std::cmp::max(1, len.div_ceil(Self::block_len()))
+    }
+}
+
+impl<P: TECurveConfig, W: pedersen::Window> CRHScheme for CRH<P, W>
+    where   P::BaseField: PrimeField,
+{
+    type Input = [u8];
+    type Output = Affine<P>;
+    type Parameters = pedersen::Parameters<Projective<P>>;
+
+    fn setup<R: Rng>(rng: &mut R) -> Result<Self::Parameters, Error> {
+        pedersen::CRH::<Projective<P>, W>::setup(rng)
+    }
+
+    fn evaluate<T: Borrow<Self::Input>>(
+        parameters: &Self::Parameters,
+        input: T
+    ) -> Result<Self::Output, Error> {
+        let input = input.borrow();
+
+        let mut state = (input.len() as u64).to_le_bytes().to_vec();
+        state.resize(Self::state_len(), 0u8);
+
+        let mut output = Affine::<P>::default();
+        for i in 0..Self::num_blocks(input.len()) {
+            let start = ark_std::cmp::min(i * Self::block_len(), input.len());
+            let end = ark_std::cmp::min(start + Self::block_len(), input.len());
+
+            let mut block = state.clone();
+            block.extend_from_slice(&input[start..end]);
+            output = pedersen::CRH::<Projective<P>, W>::evaluate(parameters, block.as_slice())?;
+
+            state.clear();
+            output.x.serialize_compressed(&mut state)?;
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::test_rng;
+    use crate::prf::config::ed_on_bw6_761::Window4x256;
+
+    type H = CRH<ark_ed_on_bw6_761::EdwardsConfig, Window4x256>;
+
+    #[test]
+    fn test_chunked_pedersen() {
+        let mut rng = test_rng();
+        let params = H::setup(&mut rng).unwrap();
+
+        // a 128-byte window fits 80 message bytes per block next to the state
+        assert_eq!(H::block_len(), 80);
+
+        // a serialized G1 point plus metadata is more than one window
+        let mut leaf = vec![];
+        ark_bls12_377::G1Affine::default().serialize_uncompressed(&mut leaf).unwrap();
+        leaf.extend_from_slice(&[1u8; 64]);
+        assert!(leaf.len() > 128);
+        let digest = H::evaluate(&params, leaf.as_slice()).unwrap();
+
+        // trailing zeros change the length, and hence the digest
+        let mut padded = leaf.clone();
+        padded.push(0);
+        assert_ne!(digest, H::evaluate(&params, padded.as_slice()).unwrap());
+        assert_ne!(
+            H::evaluate(&params, [].as_slice()).unwrap(),
+            H::evaluate(&params, [0u8].as_slice()).unwrap()
+        );
+    }
+}
@@ -0,0 +1,90 @@
+/// length-agnostic Pedersen hashing, chained over fixed-size Pedersen blocks
+pub mod chunked_pedersen;
+
+use ark_crypto_primitives::crh::{bowe_hopwood, pedersen};
+use ark_ec::{Group, twisted_edwards::{Projective, TECurveConfig}};
+use ark_ff::{BigInteger, PrimeField};
+
+use crate::utils;
+
+/// CRHs whose parameters are nothing but curve points, so they can be
+/// derived from a public domain string instead of sampled from an RNG
+pub trait JZDomainSetup {
+    type Parameters;
+
+    fn setup_from_domain(domain: &str) -> Self::Parameters;
+}
+
+/// window i starts at hash_to_curve(domain, i), and each next generator is
+/// the previous one doubled `doublings` times, as in the RNG-based setups
+fn window_generators<P: TECurveConfig, W: pedersen::Window>(
+    domain: &str,
+    doublings: usize
+) -> Vec<Vec<Projective<P>>> {
+    (0..W::NUM_WINDOWS)
+        .map(|i| {
+            let mut base = utils::hash_to_curve::<P>(domain.as_bytes(), i as u64);
+            (0..W::WINDOW_SIZE)
+                .map(|_| {
+                    let generator = base;
+                    for _ in 0..doublings {
+                        base.double_in_place();
+                    }
+                    generator
+                })
+                .collect()
+        })
+        .collect()
+}
+
+impl<P: TECurveConfig, W: pedersen::Window> JZDomainSetup for pedersen::CRH<Projective<P>, W> {
+    type Parameters = pedersen::Parameters<Projective<P>>;
+
+    fn setup_from_domain(domain: &str) -> Self::Parameters {
+        pedersen::Parameters { generators: window_generators::<P, W>(domain, 1) }
+    }
+}
+
+impl<P: TECurveConfig, W: pedersen::Window> JZDomainSetup for pedersen::TwoToOneCRH<Projective<P>, W> {
+    type Parameters = pedersen::Parameters<Projective<P>>;
+
+    fn setup_from_domain(domain: &str) -> Self::Parameters {
+        pedersen::CRH::<Projective<P>, W>::setup_from_domain(domain)
+    }
+}
+
+impl<P: TECurveConfig, W: pedersen::Window> JZDomainSetup for chunked_pedersen::CRH<P, W> {
+    type Parameters = pedersen::Parameters<Projective<P>>;
+
+    fn setup_from_domain(domain: &str) -> Self::Parameters {
+        pedersen::CRH::<Projective<P>, W>::setup_from_domain(domain)
+    }
+}
+
+impl<P: TECurveConfig, W: pedersen::Window> JZDomainSetup for bowe_hopwood::CRH<P, W> {
+    type Parameters = bowe_hopwood::Parameters<P>;
+
+    fn setup_from_domain(domain: &str) -> Self::Parameters {
+        // same bound as bowe_hopwood::CRH::setup: segment scalars stay below (p-1)/2
+        let mut range = <P::ScalarField as PrimeField>::BigInt::from(2u64);
+        for _ in 1..W::WINDOW_SIZE {
+            range.muln(4);
+        }
+        assert!(
+            range < P::ScalarField::MODULUS_MINUS_ONE_DIV_TWO,
+            "Bowe–Hopwood segment of {} chunks is too long for the scalar field",
+            W::WINDOW_SIZE
+        );
+
+        // one signed 3-bit chunk spans 4 bits of the segment scalar
+        bowe_hopwood::Parameters { generators: window_generators::<P, W>(domain, 4) }
+    }
+}
+
+impl<P: TECurveConfig, W: pedersen::Window> JZDomainSetup for bowe_hopwood::TwoToOneCRH<P, W> {
+    type Parameters = bowe_hopwood::Parameters<P>;
+
+    fn setup_from_domain(domain: &str) -> Self::Parameters {
+        bowe_hopwood::CRH::<P, W>::setup_from_domain(domain)
+    }
+}
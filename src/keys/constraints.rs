@@ -0,0 +1,220 @@
+use ark_crypto_primitives::crh::{CRHScheme, CRHSchemeGadget};
+use ark_ff::PrimeField;
+use ark_r1cs_std::prelude::*;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_relations::r1cs::*;
+use ark_std::borrow::*;
+
+use crate::prf::constraints::*;
+use super::*;
+
+/// in-circuit counterpart of prf
+pub fn prf_var<
+    H: CRHScheme,
+    HG: CRHSchemeGadget<H, ConstraintF, InputVar = [UInt8<ConstraintF>]>,
+    ConstraintF: PrimeField
+>(
+    params: &JZPRFParamsVar<H, HG, ConstraintF>,
+    domain: JZPRFDomain,
+    key: &[UInt8<ConstraintF>],
+    input: &[UInt8<ConstraintF>],
+    len: usize
+) -> Result<Vec<UInt8<ConstraintF>>> {
+    let mut prf_input = UInt8::constant_vec(&domain.to_bytes());
+    prf_input.extend_from_slice(input);
+    prf_input.extend_from_slice(key);
+
+    let output = HG::evaluate(&params.crh_params_var, &prf_input)?.to_bytes()?;
+    Ok(output[..len].to_vec())
+}
+
+fn alloc_bytes<ConstraintF: PrimeField>(
+    cs: &Namespace<ConstraintF>,
+    bytes: &[u8],
+    mode: AllocationMode
+) -> Result<Vec<UInt8<ConstraintF>>> {
+    let mut byte_vars = vec![];
+    for byte in bytes.iter() {
+        byte_vars.push(UInt8::new_variable(cs.cs(), || Ok(byte), mode)?);
+    }
+    Ok(byte_vars)
+}
+
+fn tag<ConstraintF: PrimeField>(tag: u8) -> [UInt8<ConstraintF>; 1] {
+    [UInt8::constant(tag)]
+}
+
+pub struct JZSpendingKeyVar<ConstraintF: PrimeField> {
+    pub ask: Vec<UInt8<ConstraintF>>,
+    pub nsk: Vec<UInt8<ConstraintF>>,
+}
+
+pub struct JZFullViewingKeyVar<ConstraintF: PrimeField> {
+    pub ak: Vec<UInt8<ConstraintF>>,
+    pub nk: Vec<UInt8<ConstraintF>>,
+}
+
+pub struct JZAddressVar<ConstraintF: PrimeField> {
+    pub diversifier: Vec<UInt8<ConstraintF>>,
+    pub owner: Vec<UInt8<ConstraintF>>,
+}
+
+impl<H: CRHScheme, ConstraintF: PrimeField> AllocVar<JZSpendingKey<H>, ConstraintF> for JZSpendingKeyVar<ConstraintF> {
+    fn new_variable<T: Borrow<JZSpendingKey<H>>>(
+        cs: impl Into<Namespace<ConstraintF>>,
+        f: impl FnOnce() -> Result<T>,
+        mode: AllocationMode
+    ) -> Result<Self> {
+        f().and_then(|val| {
+            let cs = cs.into();
+            let sk: &JZSpendingKey<H> = val.borrow();
+
+            Ok(JZSpendingKeyVar {
+                ask: alloc_bytes(&cs, &sk.ask, mode)?,
+                nsk: alloc_bytes(&cs, &sk.nsk, mode)?,
+            })
+        })
+    }
+}
+
+impl<H: CRHScheme, ConstraintF: PrimeField> AllocVar<JZFullViewingKey<H>, ConstraintF> for JZFullViewingKeyVar<ConstraintF> {
+    fn new_variable<T: Borrow<JZFullViewingKey<H>>>(
+        cs: impl Into<Namespace<ConstraintF>>,
+        f: impl FnOnce() -> Result<T>,
+        mode: AllocationMode
+    ) -> Result<Self> {
+        f().and_then(|val| {
+            let cs = cs.into();
+            let fvk: &JZFullViewingKey<H> = val.borrow();
+
+            Ok(JZFullViewingKeyVar {
+                ak: alloc_bytes(&cs, &fvk.ak, mode)?,
+                nk: alloc_bytes(&cs, &fvk.nk, mode)?,
+            })
+        })
+    }
+}
+
+impl<ConstraintF: PrimeField> AllocVar<JZAddress, ConstraintF> for JZAddressVar<ConstraintF> {
+    fn new_variable<T: Borrow<JZAddress>>(
+        cs: impl Into<Namespace<ConstraintF>>,
+        f: impl FnOnce() -> Result<T>,
+        mode: AllocationMode
+    ) -> Result<Self> {
+        f().and_then(|val| {
+            let cs = cs.into();
+            let address: &JZAddress = val.borrow();
+
+            Ok(JZAddressVar {
+                diversifier: alloc_bytes(&cs, &address.diversifier, mode)?,
+                owner: alloc_bytes(&cs, &address.owner, mode)?,
+            })
+        })
+    }
+}
+
+/// (ak, nk) from (ask, nsk); proving this is what spending authority means
+pub fn derive_full_viewing_key<
+    H: CRHScheme,
+    HG: CRHSchemeGadget<H, ConstraintF, InputVar = [UInt8<ConstraintF>]>,
+    ConstraintF: PrimeField
+>(
+    params: &JZPRFParamsVar<H, HG, ConstraintF>,
+    sk: &JZSpendingKeyVar<ConstraintF>
+) -> Result<JZFullViewingKeyVar<ConstraintF>> {
+    Ok(JZFullViewingKeyVar {
+        ak: prf_var(params, JZPRFDomain::KeyDerivation, &sk.ask, &tag(AK_TAG), KEY_LEN)?,
+        nk: prf_var(params, JZPRFDomain::KeyDerivation, &sk.nsk, &tag(NK_TAG), KEY_LEN)?,
+    })
+}
+
+/// enforces that the address belongs to the full viewing key; needs no
+/// spending key, so a viewing-key holder can prove it too
+pub fn enforce_address<
+    H: CRHScheme,
+    HG: CRHSchemeGadget<H, ConstraintF, InputVar = [UInt8<ConstraintF>]>,
+    ConstraintF: PrimeField
+>(
+    params: &JZPRFParamsVar<H, HG, ConstraintF>,
+    fvk: &JZFullViewingKeyVar<ConstraintF>,
+    address: &JZAddressVar<ConstraintF>
+) -> Result<()> {
+    if address.diversifier.len() != DIVERSIFIER_LEN || address.owner.len() != ADDRESS_LEN {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+
+    let ivk = prf_var(params, JZPRFDomain::KeyDerivation, &fvk.nk, &fvk.ak, KEY_LEN)?;
+    let owner = prf_var(params, JZPRFDomain::Address, &ivk, &address.diversifier, ADDRESS_LEN)?;
+    owner.enforce_equal(&address.owner)
+}
+
+/// the nullifier PRF_nk(rho) of a record, as one field element
+pub fn nullifier<
+    H: CRHScheme,
+    HG: CRHSchemeGadget<H, ConstraintF, InputVar = [UInt8<ConstraintF>]>,
+    ConstraintF: PrimeField
+>(
+    params: &JZPRFParamsVar<H, HG, ConstraintF>,
+    fvk: &JZFullViewingKeyVar<ConstraintF>,
+    rho: &[UInt8<ConstraintF>]
+) -> Result<FpVar<ConstraintF>>
+    where   HG::OutputVar: JZPRFFieldOutputVar<ConstraintF>,
+{
+    let mut prf_input = UInt8::constant_vec(&JZPRFDomain::Nullifier.to_bytes());
+    prf_input.extend_from_slice(rho);
+    prf_input.extend_from_slice(&fvk.nk);
+
+    HG::evaluate(&params.crh_params_var, &prf_input)?.to_field_var()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::test_rng;
+
+    type H = crate::prf::config::ed_on_bw6_761::Hash;
+    type HG = crate::prf::config::ed_on_bw6_761::HashGadget;
+    type ConstraintF = ark_bw6_761::Fr;
+
+    #[test]
+    fn test_key_derivation_constraints() {
+        let mut rng = test_rng();
+        let prf_params = JZPRFParams::<H>::trusted_setup(&mut rng);
+
+        let sk = JZSpendingKey::<H>::from_seed(&prf_params, &[20u8; 32]);
+        let fvk = sk.full_viewing_key();
+        let address = fvk.incoming_viewing_key().address(&[0u8; DIVERSIFIER_LEN]);
+        let rho = [9u8; 31];
+
+        let cs = ConstraintSystem::<ConstraintF>::new_ref();
+        let params_var = JZPRFParamsVar::<H, HG, ConstraintF>::new_constant(cs.clone(), &prf_params).unwrap();
+        let sk_var = JZSpendingKeyVar::new_witness(cs.clone(), || Ok(&sk)).unwrap();
+        let address_var = JZAddressVar::new_input(cs.clone(), || Ok(&address)).unwrap();
+        let rho_var = UInt8::new_witness_vec(cs.clone(), &rho).unwrap();
+
+        // spend authority: sk -> fvk -> address, plus the nullifier
+        let fvk_var = derive_full_viewing_key(&params_var, &sk_var).unwrap();
+        assert_eq!(fvk_var.ak.value().unwrap(), fvk.ak);
+        enforce_address(&params_var, &fvk_var, &address_var).unwrap();
+
+        let nullifier_var = nullifier(&params_var, &fvk_var, &rho_var).unwrap();
+        assert_eq!(nullifier_var.value().unwrap(), fvk.nullifier::<ConstraintF>(&rho));
+        assert!(cs.is_satisfied().unwrap());
+
+        // a viewing key proves the address without the spending key
+        let cs = ConstraintSystem::<ConstraintF>::new_ref();
+        let params_var = JZPRFParamsVar::<H, HG, ConstraintF>::new_constant(cs.clone(), &prf_params).unwrap();
+        let fvk_var = JZFullViewingKeyVar::new_witness(cs.clone(), || Ok(&fvk)).unwrap();
+        let other = fvk.incoming_viewing_key().address(&[1u8; DIVERSIFIER_LEN]);
+        let address_var = JZAddressVar::new_input(cs.clone(), || Ok(&other)).unwrap();
+        enforce_address(&params_var, &fvk_var, &address_var).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        // and fails for someone else's address
+        let bob = JZSpendingKey::<H>::from_seed(&prf_params, &[25u8; 32]).full_viewing_key();
+        let bob_address = bob.incoming_viewing_key().address(&[0u8; DIVERSIFIER_LEN]);
+        let address_var = JZAddressVar::new_input(cs.clone(), || Ok(&bob_address)).unwrap();
+        enforce_address(&params_var, &fvk_var, &address_var).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+}
@@ -0,0 +1,164 @@
+pub mod constraints;
+
+use ark_crypto_primitives::crh::CRHScheme;
+use ark_ff::PrimeField;
+use ark_std::borrow::Borrow;
+
+use crate::prf::*;
+
+/// length of every secret key and of the authorization key ak
+pub const KEY_LEN: usize = 32;
+/// 31 bytes always fit below the ~253 bit scalar fields, so an address can
+/// go in a record's owner field
+pub const ADDRESS_LEN: usize = 31;
+/// as in Sapling, one incoming viewing key has many unlinkable addresses
+pub const DIVERSIFIER_LEN: usize = 11;
+
+/// PRF inputs, in the KeyDerivation domain, that separate the keys derived
+/// from the same PRF key
+pub const ASK_TAG: u8 = 0;
+pub const NSK_TAG: u8 = 1;
+pub const AK_TAG: u8 = 2;
+pub const NK_TAG: u8 = 3;
+
+/// PRF_key(input) in the given domain, truncated to len bytes
+pub fn prf<H: CRHScheme + JZPRFKeyLength>(
+    params: &JZPRFParams<H>,
+    domain: JZPRFDomain,
+    key: &[u8],
+    input: &[u8],
+    len: usize
+) -> Vec<u8>
+    where   Vec<u8>: Borrow<<H as CRHScheme>::Input>,
+{
+    JZPRFInstance::<H>::new(params, domain, input, key).evaluate()[..len].to_vec()
+}
+
+/// Sapling-style key tree, with the PRF in place of the group operations:
+///
+///   seed ─┬─ ask = PRF_seed(0) ── ak = PRF_ask(2) ─┐
+///         └─ nsk = PRF_seed(1) ── nk = PRF_nsk(3) ─┴─ ivk = PRF_nk(ak) ── addr_d = PRF_ivk(d)
+///
+/// The spending key (ask, nsk) authorizes spends; the full viewing key
+/// (ak, nk) computes nullifiers and addresses but cannot prove knowledge of
+/// ask; the incoming viewing key ivk only recognizes addresses. Addresses
+/// and nullifiers use their own PRF domains, the rest KeyDerivation.
+#[derive(Derivative)]
+#[derivative(Clone(bound = "H: CRHScheme"))]
+pub struct JZSpendingKey<H: CRHScheme> {
+    pub params: JZPRFParams<H>,
+    pub ask: Vec<u8>,
+    pub nsk: Vec<u8>,
+}
+
+#[derive(Derivative)]
+#[derivative(Clone(bound = "H: CRHScheme"))]
+pub struct JZFullViewingKey<H: CRHScheme> {
+    pub params: JZPRFParams<H>,
+    pub ak: Vec<u8>,
+    pub nk: Vec<u8>,
+}
+
+#[derive(Derivative)]
+#[derivative(Clone(bound = "H: CRHScheme"))]
+pub struct JZIncomingViewingKey<H: CRHScheme> {
+    pub params: JZPRFParams<H>,
+    pub ivk: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JZAddress {
+    pub diversifier: Vec<u8>,
+    /// the value of a record's owner field
+    pub owner: Vec<u8>,
+}
+
+impl<H: CRHScheme + JZPRFKeyLength> JZSpendingKey<H>
+    where   Vec<u8>: Borrow<<H as CRHScheme>::Input>,
+{
+    pub fn from_seed(params: &JZPRFParams<H>, seed: &[u8]) -> Self {
+        JZSpendingKey {
+            params: params.clone(),
+            ask: prf(params, JZPRFDomain::KeyDerivation, seed, &[ASK_TAG], KEY_LEN),
+            nsk: prf(params, JZPRFDomain::KeyDerivation, seed, &[NSK_TAG], KEY_LEN),
+        }
+    }
+
+    pub fn full_viewing_key(&self) -> JZFullViewingKey<H> {
+        JZFullViewingKey {
+            params: self.params.clone(),
+            ak: prf(&self.params, JZPRFDomain::KeyDerivation, &self.ask, &[AK_TAG], KEY_LEN),
+            nk: prf(&self.params, JZPRFDomain::KeyDerivation, &self.nsk, &[NK_TAG], KEY_LEN),
+        }
+    }
+}
+
+impl<H: CRHScheme + JZPRFKeyLength> JZFullViewingKey<H>
+    where   Vec<u8>: Borrow<<H as CRHScheme>::Input>,
+{
+    pub fn incoming_viewing_key(&self) -> JZIncomingViewingKey<H> {
+        JZIncomingViewingKey {
+            params: self.params.clone(),
+            ivk: prf(&self.params, JZPRFDomain::KeyDerivation, &self.nk, &self.ak, KEY_LEN),
+        }
+    }
+
+    /// the nullifier PRF_nk(rho) of a record, as one field element
+    pub fn nullifier<F: PrimeField>(&self, rho: &[u8]) -> F
+        where   <H as CRHScheme>::Output: JZPRFFieldOutput<F>,
+    {
+        JZPRFInstance::<H>::new(&self.params, JZPRFDomain::Nullifier, rho, &self.nk).evaluate_field()
+    }
+}
+
+impl<H: CRHScheme + JZPRFKeyLength> JZIncomingViewingKey<H>
+    where   Vec<u8>: Borrow<<H as CRHScheme>::Input>,
+{
+    pub fn address(&self, diversifier: &[u8; DIVERSIFIER_LEN]) -> JZAddress {
+        JZAddress {
+            diversifier: diversifier.to_vec(),
+            owner: prf(&self.params, JZPRFDomain::Address, &self.ivk, diversifier, ADDRESS_LEN),
+        }
+    }
+
+    /// true iff the address was derived from this key
+    pub fn owns(&self, address: &JZAddress) -> bool {
+        address.diversifier.len() == DIVERSIFIER_LEN
+            && prf(&self.params, JZPRFDomain::Address, &self.ivk, &address.diversifier, ADDRESS_LEN) == address.owner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::test_rng;
+
+    type H = crate::prf::config::ed_on_bw6_761::Hash;
+
+    #[test]
+    fn test_key_derivation() {
+        let mut rng = test_rng();
+        let prf_params = JZPRFParams::<H>::trusted_setup(&mut rng);
+
+        let sk = JZSpendingKey::<H>::from_seed(&prf_params, &[20u8; 32]);
+        let fvk = sk.full_viewing_key();
+        let ivk = fvk.incoming_viewing_key();
+
+        let address = ivk.address(&[0u8; DIVERSIFIER_LEN]);
+        assert_eq!(address.owner.len(), ADDRESS_LEN);
+        assert!(ivk.owns(&address));
+
+        // diversified addresses are distinct but recognized by the same ivk
+        let other = ivk.address(&[1u8; DIVERSIFIER_LEN]);
+        assert_ne!(address.owner, other.owner);
+        assert!(ivk.owns(&other));
+
+        // another seed yields unrelated keys
+        let bob = JZSpendingKey::<H>::from_seed(&prf_params, &[25u8; 32]).full_viewing_key();
+        assert!(!bob.incoming_viewing_key().owns(&address));
+        assert_ne!(
+            fvk.nullifier::<ark_bw6_761::Fr>(&[9u8; 31]),
+            bob.nullifier::<ark_bw6_761::Fr>(&[9u8; 31])
+        );
+    }
+}
@@ -12,8 +12,12 @@
 #[macro_use]
 extern crate derivative;
 
+pub mod codec;
+pub mod crh;
 pub mod merkle_tree;
 pub mod vector_commitment;
 pub mod record_commitment;
 pub mod prf;
+pub mod keys;
+pub mod transcript;
 pub mod utils;
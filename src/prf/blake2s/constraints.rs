@@ -0,0 +1,148 @@
+use ark_crypto_primitives::crh::{CRHSchemeGadget, sha256::constraints::UnitVar};
+use ark_crypto_primitives::prf::blake2s::constraints::evaluate_blake2s;
+use ark_ff::PrimeField;
+use ark_r1cs_std::prelude::*;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_relations::r1cs::*;
+use ark_std::{borrow::*, marker::PhantomData};
+
+use super::{PRF, key_first};
+use crate::prf::constraints::JZPRFFieldOutputVar;
+
+pub struct PRFGadget<F: PrimeField> {
+    field_phantom: PhantomData<F>,
+}
+
+/// the 32 output bytes, in the order of the native digest
+#[derive(Clone, Debug)]
+pub struct OutputVar<F: PrimeField>(pub Vec<UInt8<F>>);
+
+impl<F: PrimeField> EqGadget<F> for OutputVar<F> {
+    fn is_eq(&self, other: &Self) -> Result<Boolean<F>> {
+        self.0.is_eq(&other.0)
+    }
+}
+
+impl<F: PrimeField> ToBytesGadget<F> for OutputVar<F> {
+    fn to_bytes(&self) -> Result<Vec<UInt8<F>>> {
+        Ok(self.0.clone())
+    }
+}
+
+impl<F: PrimeField> CondSelectGadget<F> for OutputVar<F> {
+    fn conditionally_select(
+        cond: &Boolean<F>,
+        true_value: &Self,
+        false_value: &Self
+    ) -> Result<Self> {
+        true_value.0
+            .iter()
+            .zip(false_value.0.iter())
+            .map(|(t, f)| UInt8::conditionally_select(cond, t, f))
+            .collect::<Result<Vec<_>>>()
+            .map(OutputVar)
+    }
+}
+
+impl<F: PrimeField> AllocVar<[u8; 32], F> for OutputVar<F> {
+    fn new_variable<T: Borrow<[u8; 32]>>(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<T>,
+        mode: AllocationMode
+    ) -> Result<Self> {
+        let cs = cs.into();
+        let bytes = f().map(|b| *b.borrow());
+
+        let mut byte_vars = vec![];
+        for i in 0..32 {
+            byte_vars.push(UInt8::new_variable(
+                cs.clone(),
+                || bytes.as_ref().map(|b| b[i]).map_err(|e| *e),
+                mode
+            )?);
+        }
+        Ok(OutputVar(byte_vars))
+    }
+}
+
+impl<F: PrimeField> R1CSVar<F> for OutputVar<F> {
+    type Value = [u8; 32];
+
+    fn cs(&self) -> ConstraintSystemRef<F> {
+        self.0.cs()
+    }
+
+    fn value(&self) -> Result<Self::Value> {
+        let mut value = [0u8; 32];
+        for (v, byte) in value.iter_mut().zip(self.0.iter()) {
+            *v = byte.value()?;
+        }
+        Ok(value)
+    }
+}
+
+impl<F: PrimeField> JZPRFFieldOutputVar<F> for OutputVar<F> {
+    /// the digest may exceed the modulus, so it is split below the modulus
+    /// size to skip le_bits_to_fp_var's range check and recombined mod p
+    fn to_field_var(&self) -> Result<FpVar<F>> {
+        let bits = self.0.to_bits_le()?;
+        let split = (F::MODULUS_BIT_SIZE - 1) as usize;
+
+        let low = Boolean::le_bits_to_fp_var(&bits[..split])?;
+        let high = Boolean::le_bits_to_fp_var(&bits[split..])?;
+        Ok(low + high * F::from(2u8).pow([split as u64]))
+    }
+}
+
+impl<F: PrimeField> CRHSchemeGadget<PRF, F> for PRFGadget<F> {
+    type InputVar = [UInt8<F>];
+    type OutputVar = OutputVar<F>;
+    type ParametersVar = UnitVar<F>;
+
+    fn evaluate(
+        _parameters: &Self::ParametersVar,
+        input: &Self::InputVar
+    ) -> Result<Self::OutputVar> {
+        let message = key_first(input).ok_or(SynthesisError::Unsatisfiable)?;
+
+        let mut bits = vec![];
+        for byte in message.iter() {
+            bits.extend(byte.to_bits_le()?);
+        }
+
+        let mut output = vec![];
+        for word in evaluate_blake2s(&bits)?.iter() {
+            output.extend(word.to_bytes()?);
+        }
+        Ok(OutputVar(output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prf::{*, constraints::*};
+    use ark_r1cs_std::prelude::*;
+    use ark_relations::r1cs::*;
+    use ark_std::test_rng;
+
+    type H = config::blake2s::Hash;
+    type HG = config::blake2s::HashGadget<ConstraintF>;
+    type ConstraintF = ark_bls12_377::Fr;
+
+    #[test]
+    fn test_blake2s_prf_constraints() {
+        let mut rng = test_rng();
+        let prf_params = JZPRFParams::<H>::trusted_setup(&mut rng);
+        let instance = JZPRFInstance::<H>::new(&prf_params, JZPRFDomain::Address, &[0u8; 32], &[25u8; 32]);
+
+        let cs = ConstraintSystem::<ConstraintF>::new_ref();
+        let params_var = JZPRFParamsVar::<H, HG, ConstraintF>::new_constant(cs.clone(), &prf_params).unwrap();
+        let instance_var = JZPRFInstanceVar::<ConstraintF>::new_witness(cs.clone(), || Ok(&instance)).unwrap();
+
+        generate_constraints(cs.clone(), &params_var, &instance_var);
+
+        let output_var = generate_field_constraints(cs.clone(), &params_var, &instance_var).unwrap();
+        assert_eq!(output_var.value().unwrap(), instance.evaluate_field::<ConstraintF>());
+        assert!(cs.is_satisfied().unwrap());
+    }
+}
@@ -0,0 +1,83 @@
+pub mod constraints;
+
+use ark_crypto_primitives::crh::CRHScheme;
+use ark_crypto_primitives::Error;
+use ark_std::{borrow::Borrow, rand::Rng};
+use blake2::{Blake2s256, Digest};
+
+use super::JZPRFKeyLength;
+
+/// length of the PRF key, which JZPRFInstance appends to the input
+pub const KEY_LEN: usize = 32;
+
+/// BLAKE2s PRF of Zexe, PRF_k(x) = BLAKE2s-256(k || x). JZPRFInstance hashes
+/// domain || input || key, so the trailing KEY_LEN bytes are moved in front;
+/// when domain || input is 32 bytes this matches ark_crypto_primitives'
+/// Blake2s PRF.
+pub struct PRF;
+
+/// JZPRFInstance::new rejects keys that are not KEY_LEN bytes, so the
+/// trailing KEY_LEN bytes of its input are always the key
+impl JZPRFKeyLength for PRF {
+    const KEY_LEN: Option<usize> = Some(KEY_LEN);
+}
+
+/// k || x from x || k, taking the last KEY_LEN bytes as the key
+pub fn key_first<T: Clone>(input: &[T]) -> Option<Vec<T>> {
+    if input.len() < KEY_LEN {
+        return None;
+    }
+    let (x, k) = input.split_at(input.len() - KEY_LEN);
+    Some([k, x].concat())
+}
+
+impl CRHScheme for PRF {
+    type Input = [u8];
+    type Output = [u8; 32];
+    type Parameters = ();
+
+    fn setup<R: Rng>(_rng: &mut R) -> Result<Self::Parameters, Error> {
+        Ok(())
+    }
+
+    fn evaluate<T: Borrow<Self::Input>>(
+        _parameters: &Self::Parameters,
+        input: T
+    ) -> Result<Self::Output, Error> {
+        let message = key_first(input.borrow()).ok_or("BLAKE2s PRF input is shorter than its 32-byte key")?;
+
+        Ok(Blake2s256::digest(&message).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prf::*;
+    use ark_crypto_primitives::prf::{PRF, blake2s::Blake2s};
+    use ark_std::test_rng;
+
+    type H = config::blake2s::Hash;
+
+    #[test]
+    fn test_blake2s_prf() {
+        let mut rng = test_rng();
+        let prf_params = JZPRFParams::<H>::trusted_setup(&mut rng);
+
+        let input = [0u8; 31];
+        let key = [25u8; 32];
+
+        let output = JZPRFInstance::<H>::new(&prf_params, JZPRFDomain::Address, &input, &key).evaluate();
+        let tagged: [u8; 32] = [JZPRFDomain::Address.to_bytes(), input.to_vec()].concat().try_into().unwrap();
+        assert_eq!(output, Blake2s::evaluate(&key, &tagged).unwrap().to_vec());
+    }
+
+    #[test]
+    #[should_panic(expected = "PRF key must be 32 bytes")]
+    fn test_blake2s_prf_key_length() {
+        let mut rng = test_rng();
+        let prf_params = JZPRFParams::<H>::trusted_setup(&mut rng);
+
+        // a short key would otherwise be mis-split from the input
+        JZPRFInstance::<H>::new(&prf_params, JZPRFDomain::Address, &[0u8; 32], &[25u8; 31]);
+    }
+}
@@ -0,0 +1,9 @@
+use crate::prf::blake2s;
+
+/// BLAKE2s works over bytes, so the same PRF serves every constraint field.
+/// Outputs match Zexe's Blake2s PRF only when the domain tag and the input
+/// are 32 bytes together, e.g. Address with a 31-byte input; other lengths
+/// give a PRF of this crate alone
+pub type Hash = blake2s::PRF;
+
+pub type HashGadget<F> = blake2s::constraints::PRFGadget<F>;
@@ -1,4 +1,4 @@
-use ark_crypto_primitives::crh::pedersen;
+use ark_crypto_primitives::crh::{bowe_hopwood, pedersen};
 
 #[derive(Clone)]
 pub struct Window4x256;
@@ -7,6 +7,9 @@ impl pedersen::Window for Window4x256 {
     const NUM_WINDOWS: usize = 256;
 }
 
+/// the curve of the Pedersen hash, also used by prf::vrf
+pub type Curve = ark_ed_on_bls12_377::EdwardsConfig;
+
 pub type Hash = pedersen::CRH<
     ark_ed_on_bls12_377::EdwardsProjective, Window4x256
 >;
@@ -17,4 +20,22 @@ pub type HashGadget = pedersen::constraints::CRHGadget<
     Window4x256
 >;
 
-//pub type HOutput = <ark_ed_on_bls12_377::EdwardsConfig as CurveConfig>::BaseField;
\ No newline at end of file
+//pub type HOutput = <ark_ed_on_bls12_377::EdwardsConfig as CurveConfig>::BaseField;
+/// for PRF inputs longer than the 128 bytes of Window4x256
+pub type ChunkedHash = crate::crh::chunked_pedersen::CRH<Curve, Window4x256>;
+
+pub type ChunkedHashGadget = crate::crh::chunked_pedersen::constraints::CRHGadget<Curve, Window4x256>;
+
+/// Bowe–Hopwood segments of 57 signed 3-bit chunks; 6 of them cover the
+/// 1024 input bits of Window4x256 (1026 bits)
+#[derive(Clone)]
+pub struct BhWindow57x6;
+impl pedersen::Window for BhWindow57x6 {
+    const WINDOW_SIZE: usize = 57;
+    const NUM_WINDOWS: usize = 6;
+}
+
+/// Bowe–Hopwood Pedersen: the x-coordinate output is a field element
+pub type BhHash = bowe_hopwood::CRH<Curve, BhWindow57x6>;
+
+pub type BhHashGadget = bowe_hopwood::constraints::CRHGadget<Curve, ark_ed_on_bls12_377::constraints::FqVar>;
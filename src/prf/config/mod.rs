@@ -1,2 +1,5 @@
+pub mod blake2s;
 pub mod ed_on_bls12_377;
 pub mod ed_on_bw6_761;
+pub mod poseidon_bls12_377;
+pub mod poseidon_bw6_761;
@@ -0,0 +1,7 @@
+use crate::prf::poseidon;
+
+/// keyed Poseidon over the scalar field of BLS12-377, i.e. the constraint
+/// field of circuits proven over BLS12-377
+pub type Hash = poseidon::PRF<ark_bls12_377::Fr>;
+
+pub type HashGadget = poseidon::constraints::PRFGadget<ark_bls12_377::Fr>;
@@ -0,0 +1,7 @@
+use crate::prf::poseidon;
+
+/// keyed Poseidon over the scalar field of BW6-761, i.e. the constraint
+/// field of circuits proven over BW6-761
+pub type Hash = poseidon::PRF<ark_bw6_761::Fr>;
+
+pub type HashGadget = poseidon::constraints::PRFGadget<ark_bw6_761::Fr>;
@@ -1,12 +1,34 @@
 use ark_crypto_primitives::crh::CRHSchemeGadget;
+use ark_ec::twisted_edwards::TECurveConfig;
 use ark_ff::PrimeField;
 use ark_r1cs_std::prelude::*;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::groups::curves::twisted_edwards::AffineVar;
 use ark_std::borrow::*;
 use ark_relations::r1cs::*;
 use ark_r1cs_std::{bits::uint8::UInt8, bits::ToBytesGadget, alloc::AllocVar};
 
 use super::*;
 
+/// in-circuit counterpart of JZPRFFieldOutput
+pub trait JZPRFFieldOutputVar<ConstraintF: PrimeField> {
+    fn to_field_var(&self) -> Result<FpVar<ConstraintF>>;
+}
+
+impl<P: TECurveConfig> JZPRFFieldOutputVar<P::BaseField> for AffineVar<P, FpVar<P::BaseField>>
+    where   P::BaseField: PrimeField,
+{
+    fn to_field_var(&self) -> Result<FpVar<P::BaseField>> {
+        Ok(self.x.clone())
+    }
+}
+
+impl<ConstraintF: PrimeField> JZPRFFieldOutputVar<ConstraintF> for FpVar<ConstraintF> {
+    fn to_field_var(&self) -> Result<FpVar<ConstraintF>> {
+        Ok(self.clone())
+    }
+}
+
 pub struct JZPRFParamsVar<H: CRHScheme, HG: CRHSchemeGadget<H, ConstraintF>, ConstraintF: PrimeField> {
     pub crh_params_var: <HG as CRHSchemeGadget<H, ConstraintF>>::ParametersVar,
 }
@@ -35,6 +57,8 @@ AllocVar<JZPRFParams<H>, ConstraintF> for JZPRFParamsVar<H, HG, ConstraintF> {
 }
 
 pub struct JZPRFInstanceVar<ConstraintF: PrimeField> {
+    /// the domain tag is public by construction, so it is always a constant
+    pub domain_var: Vec<UInt8<ConstraintF>>,
     pub input_var: Vec<UInt8<ConstraintF>>,
     pub key_var: Vec<UInt8<ConstraintF>>,
     pub output_var: Vec<UInt8<ConstraintF>>,
@@ -53,6 +77,8 @@ impl<H: CRHScheme, ConstraintF: PrimeField> AllocVar<JZPRFInstance<H>, Constrain
             
             let prf_instance: &JZPRFInstance<H> = val.borrow();
 
+            let domain_byte_vars = UInt8::<ConstraintF>::constant_vec(&prf_instance.domain.to_bytes());
+
             let mut input_byte_vars = Vec::<UInt8<ConstraintF>>::new();
             for byte in prf_instance.input.iter() {
                 input_byte_vars.push(UInt8::<ConstraintF>::new_variable(
@@ -84,6 +110,7 @@ impl<H: CRHScheme, ConstraintF: PrimeField> AllocVar<JZPRFInstance<H>, Constrain
 
             Ok(
                 JZPRFInstanceVar {
+                    domain_var: domain_byte_vars,
                     input_var: input_byte_vars,
                     key_var: key_byte_vars,
                     output_var: output_byte_vars,
@@ -106,6 +133,7 @@ pub fn generate_constraints<
 )
 {
     let mut input = vec![];
+    input.extend_from_slice(&prf_instance.domain_var);
     input.extend_from_slice(&prf_instance.input_var);
     input.extend_from_slice(&prf_instance.key_var);
 
@@ -121,3 +149,65 @@ pub fn generate_constraints<
         byte_var.enforce_equal(&prf_instance.output_var[i]).unwrap();
     }
 }
+
+/// evaluates the PRF in-circuit and returns its canonical field output,
+/// e.g. to enforce equality with a nullifier allocated as a public input
+pub fn generate_field_constraints<
+    H: CRHScheme,
+    HG: CRHSchemeGadget<H, ConstraintF, InputVar = [UInt8<ConstraintF>]>,
+    ConstraintF: PrimeField
+>
+(
+    _cs: ConstraintSystemRef<ConstraintF>,
+    params: &JZPRFParamsVar<H, HG, ConstraintF>,
+    prf_instance: &JZPRFInstanceVar<ConstraintF>,
+) -> Result<FpVar<ConstraintF>>
+    where   HG::OutputVar: JZPRFFieldOutputVar<ConstraintF>,
+{
+    let mut input = vec![];
+    input.extend_from_slice(&prf_instance.domain_var);
+    input.extend_from_slice(&prf_instance.input_var);
+    input.extend_from_slice(&prf_instance.key_var);
+
+    HG::evaluate(&params.crh_params_var, &input)?.to_field_var()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type ConstraintF = ark_bw6_761::Fr;
+
+    fn field_constraints<H, HG>(input: &[u8]) -> usize
+        where   H: CRHScheme<Input = [u8]> + JZDomainSetup<Parameters = <H as CRHScheme>::Parameters> + JZPRFKeyLength,
+                <H as CRHScheme>::Output: JZPRFFieldOutput<ConstraintF>,
+                HG: CRHSchemeGadget<H, ConstraintF, InputVar = [UInt8<ConstraintF>]>,
+                HG::OutputVar: JZPRFFieldOutputVar<ConstraintF>,
+    {
+        let prf_params = JZPRFParams::<H>::from_domain(DEFAULT_DOMAIN);
+        let instance = JZPRFInstance::<H>::new(&prf_params, JZPRFDomain::Nullifier, input, &[25u8; 32]);
+
+        let cs = ConstraintSystem::<ConstraintF>::new_ref();
+        let params_var = JZPRFParamsVar::<H, HG, ConstraintF>::new_constant(cs.clone(), &prf_params).unwrap();
+        let instance_var = JZPRFInstanceVar::<ConstraintF>::new_witness(cs.clone(), || Ok(&instance)).unwrap();
+        let before = cs.num_constraints();
+
+        let output_var = generate_field_constraints(cs.clone(), &params_var, &instance_var).unwrap();
+        assert_eq!(output_var.value().unwrap(), instance.evaluate_field::<ConstraintF>());
+        assert!(cs.is_satisfied().unwrap());
+        cs.num_constraints() - before
+    }
+
+    #[test]
+    fn test_bowe_hopwood_prf_constraints() {
+        let window4 = field_constraints::<config::ed_on_bw6_761::Hash, config::ed_on_bw6_761::HashGadget>(&[7u8; 32]);
+        let bowe_hopwood = field_constraints::<config::ed_on_bw6_761::BhHash, config::ed_on_bw6_761::BhHashGadget>(&[7u8; 32]);
+        assert_eq!((window4, bowe_hopwood), (2801, 875));
+    }
+
+    #[test]
+    fn test_chunked_prf_constraints() {
+        // 200 bytes of input plus the key, beyond the 128 bytes of Window4x256
+        field_constraints::<config::ed_on_bw6_761::ChunkedHash, config::ed_on_bw6_761::ChunkedHashGadget>(&[7u8; 200]);
+    }
+}
@@ -1,11 +1,88 @@
 pub mod constraints;
+pub mod blake2s;
 pub mod config;
+pub mod poseidon;
+pub mod vrf;
 
-use ark_crypto_primitives::crh::CRHScheme;
+use ark_crypto_primitives::crh::{CRHScheme, bowe_hopwood, pedersen};
+use ark_ec::CurveGroup;
+use ark_ec::twisted_edwards::{Affine, TECurveConfig};
+use ark_ff::{Fp, FpConfig, PrimeField};
 use ark_std::rand::Rng;
 use ark_serialize::*;
 use std::io::Cursor;
 
+use crate::crh::JZDomainSetup;
+
+pub const DEFAULT_DOMAIN: &str = "jzexe.prf";
+
+/// PRF outputs that map to one canonical element of the circuit's field,
+/// e.g. so a nullifier is a single public input
+pub trait JZPRFFieldOutput<F: PrimeField> {
+    fn to_field(&self) -> F;
+}
+
+/// the x-coordinate of a Pedersen output, which lies in the circuit's field
+impl<P: TECurveConfig> JZPRFFieldOutput<P::BaseField> for Affine<P>
+    where   P::BaseField: PrimeField,
+{
+    fn to_field(&self) -> P::BaseField {
+        self.x
+    }
+}
+
+/// Poseidon outputs are already field elements
+impl<P: FpConfig<N>, const N: usize> JZPRFFieldOutput<Fp<P, N>> for Fp<P, N> {
+    fn to_field(&self) -> Fp<P, N> {
+        *self
+    }
+}
+
+/// byte digests are read little-endian and reduced modulo the field
+impl<F: PrimeField> JZPRFFieldOutput<F> for [u8; 32] {
+    fn to_field(&self) -> F {
+        F::from_le_bytes_mod_order(self)
+    }
+}
+
+/// the key length a PRF requires, if any; JZPRFInstance::new panics on
+/// keys of any other length
+pub trait JZPRFKeyLength {
+    const KEY_LEN: Option<usize> = None;
+}
+
+/// the Pedersen hashes absorb the key like any other input bytes
+impl<C: CurveGroup, W: pedersen::Window> JZPRFKeyLength for pedersen::CRH<C, W> {}
+
+impl<P: TECurveConfig, W: pedersen::Window> JZPRFKeyLength for bowe_hopwood::CRH<P, W> {}
+
+impl<P: TECurveConfig, W: pedersen::Window> JZPRFKeyLength for crate::crh::chunked_pedersen::CRH<P, W> {}
+
+/// What a PRF output is used for. The tag is hashed in front of the input,
+/// so outputs of different domains never collide, whatever the inputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JZPRFDomain {
+    Address,
+    Nullifier,
+    CommitmentRandomness,
+    /// the internal nodes of the keys module's key tree
+    KeyDerivation,
+    /// application-defined domains
+    Custom(u32),
+}
+
+impl JZPRFDomain {
+    /// a prefix-free encoding: one byte per variant, then the custom id
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            JZPRFDomain::Address => vec![1],
+            JZPRFDomain::Nullifier => vec![2],
+            JZPRFDomain::CommitmentRandomness => vec![3],
+            JZPRFDomain::KeyDerivation => vec![4],
+            JZPRFDomain::Custom(id) => [vec![0xff], id.to_le_bytes().to_vec()].concat(),
+        }
+    }
+}
 
 #[derive(Derivative)]
 #[derivative(Clone(bound = "H: CRHScheme"))]
@@ -21,40 +98,67 @@ impl<H: CRHScheme> JZPRFParams<H> {
     }
 }
 
+impl<H> JZPRFParams<H>
+    where   H: CRHScheme + JZDomainSetup<Parameters = <H as CRHScheme>::Parameters>,
+{
+    /// derives the Pedersen generators by hashing to the curve, so the PRF
+    /// needs no trusted party
+    pub fn from_domain(domain: &str) -> Self {
+        JZPRFParams {
+            crh_params: H::setup_from_domain(domain),
+        }
+    }
+}
+
 pub struct JZPRFInstance<H: CRHScheme>
     where   Vec<u8>: std::borrow::Borrow<<H as CRHScheme>::Input>,
 {
     pub params: JZPRFParams<H>,
+    pub domain: JZPRFDomain,
     pub input: Vec<u8>,
     pub key: Vec<u8>,
 }
 
-impl<H: CRHScheme> JZPRFInstance<H>
+impl<H: CRHScheme + JZPRFKeyLength> JZPRFInstance<H>
     where   Vec<u8>: std::borrow::Borrow<<H as CRHScheme>::Input>,
 {
+    /// panics if `key` does not have the length H requires, see JZPRFKeyLength
     pub fn new(
         params: &JZPRFParams<H>,
+        domain: JZPRFDomain,
         input: &[u8],
         key: &[u8],
     ) -> Self {
+        if let Some(key_len) = H::KEY_LEN {
+            assert_eq!(key.len(), key_len, "PRF key must be {} bytes", key_len);
+        }
+
         JZPRFInstance {
             params: params.clone(),
+            domain,
             input: input.to_vec(),
             key: key.to_vec(),
         }
     }
+}
 
-    pub fn evaluate(&self) -> Vec<u8> {
-        let mut prf_input = vec![];
+impl<H: CRHScheme> JZPRFInstance<H>
+    where   Vec<u8>: std::borrow::Borrow<<H as CRHScheme>::Input>,
+{
+    fn crh_output(&self) -> <H as CRHScheme>::Output {
+        let mut prf_input = self.domain.to_bytes();
         prf_input.extend_from_slice(&self.input);
         prf_input.extend_from_slice(&self.key);
 
-        let crh_output: <H as CRHScheme>::Output =
-            <H as CRHScheme>::evaluate(
-                &self.params.crh_params,
-                prf_input
-            )
-            .unwrap();
+        <H as CRHScheme>::evaluate(
+            &self.params.crh_params,
+            prf_input
+        )
+        .unwrap()
+    }
+
+    pub fn evaluate(&self) -> Vec<u8> {
+        let crh_output = self.crh_output();
 
         // the output of the PRF only contains the x coordinate
         let mut serialized = vec![0; crh_output.serialized_size(ark_serialize::Compress::No)];
@@ -63,6 +167,13 @@ impl<H: CRHScheme> JZPRFInstance<H>
 
         serialized
     }
+
+    /// the output as a canonical field element, see JZPRFFieldOutput
+    pub fn evaluate_field<F: PrimeField>(&self) -> F
+        where   <H as CRHScheme>::Output: JZPRFFieldOutput<F>,
+    {
+        self.crh_output().to_field()
+    }
 }
 
 #[cfg(test)]
@@ -84,7 +195,48 @@ mod tests {
         let input = [0u8; 32];
         let key = [25u8; 32];
 
-        let instance = JZPRFInstance::<H>::new(&prf_params, &input, &key);
-        let _prf_output = instance.evaluate();
+        let instance = JZPRFInstance::<H>::new(&prf_params, JZPRFDomain::Address, &input, &key);
+        let prf_output = instance.evaluate();
+
+        // the field output is the x-coordinate, serialized first
+        let x: ark_bw6_761::Fr = instance.evaluate_field();
+        assert_eq!(x, ark_bw6_761::Fr::from_le_bytes_mod_order(&prf_output[..48]));
+
+        // the same key and input in another domain give an unrelated output
+        for domain in [JZPRFDomain::Nullifier, JZPRFDomain::Custom(1)] {
+            assert_ne!(JZPRFInstance::<H>::new(&prf_params, domain, &input, &key).evaluate(), prf_output);
+        }
+    }
+
+    #[test]
+    fn test_chunked_prf() {
+        type CH = config::ed_on_bw6_761::ChunkedHash;
+
+        let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
+        let prf_params = JZPRFParams::<CH>::trusted_setup(&mut rng);
+
+        // 200 bytes of input plus the key overflow a single Pedersen window
+        let input = [7u8; 200];
+        let key = [25u8; 32];
+        let instance = JZPRFInstance::<CH>::new(&prf_params, JZPRFDomain::Nullifier, &input, &key);
+
+        // the PRF hashes domain || input || key
+        let message = [JZPRFDomain::Nullifier.to_bytes(), input.to_vec(), key.to_vec()].concat();
+        let expected = CH::evaluate(&prf_params.crh_params, message).unwrap();
+        assert_eq!(instance.evaluate_field::<ark_bw6_761::Fr>(), expected.x);
+    }
+
+    #[test]
+    fn test_prf_from_domain() {
+        let prf_params = JZPRFParams::<H>::from_domain(DEFAULT_DOMAIN);
+        let again = JZPRFParams::<H>::from_domain(DEFAULT_DOMAIN);
+        let other = JZPRFParams::<H>::from_domain("jzexe.prf.other");
+        assert_eq!(prf_params.crh_params.generators, again.crh_params.generators);
+
+        let input = [0u8; 32];
+        let key = [25u8; 32];
+        let output = JZPRFInstance::<H>::new(&prf_params, JZPRFDomain::Address, &input, &key).evaluate();
+        assert_eq!(output, JZPRFInstance::<H>::new(&again, JZPRFDomain::Address, &input, &key).evaluate());
+        assert_ne!(output, JZPRFInstance::<H>::new(&other, JZPRFDomain::Address, &input, &key).evaluate());
     }
 }
@@ -0,0 +1,66 @@
+use ark_crypto_primitives::crh::{CRHSchemeGadget, poseidon::constraints::*};
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ff::PrimeField;
+use ark_r1cs_std::prelude::*;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_relations::r1cs::*;
+use ark_std::marker::PhantomData;
+
+use super::{PRF, CHUNK_LEN};
+
+pub struct PRFGadget<F: PrimeField + Absorb> {
+    field_phantom: PhantomData<F>,
+}
+
+/// in-circuit counterpart of pack_bytes; the byte length is fixed at
+/// allocation time, so the length element is a constant
+pub fn pack_byte_vars<F: PrimeField>(bytes: &[UInt8<F>]) -> Result<Vec<FpVar<F>>> {
+    let mut elements = vec![FpVar::<F>::constant(F::from(bytes.len() as u64))];
+    for chunk in bytes.chunks(CHUNK_LEN) {
+        let bits = chunk.to_bits_le()?;
+        elements.push(Boolean::le_bits_to_fp_var(&bits)?);
+    }
+    Ok(elements)
+}
+
+impl<F: PrimeField + Absorb> CRHSchemeGadget<PRF<F>, F> for PRFGadget<F> {
+    type InputVar = [UInt8<F>];
+    type OutputVar = FpVar<F>;
+    type ParametersVar = CRHParametersVar<F>;
+
+    fn evaluate(
+        parameters: &Self::ParametersVar,
+        input: &Self::InputVar
+    ) -> Result<Self::OutputVar> {
+        CRHGadget::<F>::evaluate(parameters, &pack_byte_vars(input)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prf::{*, constraints::*};
+    use ark_r1cs_std::prelude::*;
+    use ark_relations::r1cs::*;
+    use ark_std::test_rng;
+
+    type H = config::poseidon_bls12_377::Hash;
+    type HG = config::poseidon_bls12_377::HashGadget;
+    type ConstraintF = ark_bls12_377::Fr;
+
+    #[test]
+    fn test_poseidon_prf_constraints() {
+        let mut rng = test_rng();
+        let prf_params = JZPRFParams::<H>::trusted_setup(&mut rng);
+        let instance = JZPRFInstance::<H>::new(&prf_params, JZPRFDomain::Address, &[0u8; 32], &[25u8; 32]);
+
+        let cs = ConstraintSystem::<ConstraintF>::new_ref();
+        let params_var = JZPRFParamsVar::<H, HG, ConstraintF>::new_constant(cs.clone(), &prf_params).unwrap();
+        let instance_var = JZPRFInstanceVar::<ConstraintF>::new_witness(cs.clone(), || Ok(&instance)).unwrap();
+
+        generate_constraints(cs.clone(), &params_var, &instance_var);
+
+        let output_var = generate_field_constraints(cs.clone(), &params_var, &instance_var).unwrap();
+        assert_eq!(output_var.value().unwrap(), instance.evaluate_field::<ConstraintF>());
+        assert!(cs.is_satisfied().unwrap());
+    }
+}
@@ -0,0 +1,81 @@
+pub mod constraints;
+
+use ark_crypto_primitives::crh::{CRHScheme, poseidon};
+use ark_crypto_primitives::sponge::{Absorb, poseidon::PoseidonConfig};
+use ark_crypto_primitives::Error;
+use ark_ff::PrimeField;
+use ark_std::{borrow::Borrow, marker::PhantomData, rand::Rng};
+
+use crate::utils;
+use super::JZPRFKeyLength;
+
+/// bytes per absorbed field element; 31 bytes are below every modulus we
+/// use, so the packing is injective
+pub const CHUNK_LEN: usize = 31;
+
+/// Keyed Poseidon: the PRF input || key bytes are packed into field
+/// elements, prefixed with their byte length, and absorbed into a Poseidon
+/// sponge whose first squeezed element is the output. A sponge keyed with
+/// secret material is a PRF, unlike the Pedersen CRH, and costs a few
+/// hundred constraints per absorbed element in-circuit.
+pub struct PRF<F: PrimeField + Absorb> {
+    field_phantom: PhantomData<F>,
+}
+
+/// packs the bytes into CHUNK_LEN little-endian field elements, after the
+/// length element that keeps inputs of different lengths apart
+pub fn pack_bytes<F: PrimeField>(bytes: &[u8]) -> Vec<F> {
+    let mut elements = vec![F::from(bytes.len() as u64)];
+    for chunk in bytes.chunks(CHUNK_LEN) {
+        elements.push(F::from_le_bytes_mod_order(chunk));
+    }
+    elements
+}
+
+/// the key is packed like the input, so it may have any length
+impl<F: PrimeField + Absorb> JZPRFKeyLength for PRF<F> {}
+
+impl<F: PrimeField + Absorb> CRHScheme for PRF<F> {
+    type Input = [u8];
+    type Output = F;
+    type Parameters = PoseidonConfig<F>;
+
+    /// the Poseidon constants are derived deterministically, so the rng is
+    /// not used
+    fn setup<R: Rng>(_rng: &mut R) -> Result<Self::Parameters, Error> {
+        Ok(utils::poseidon_parameters::<F>(2))
+    }
+
+    fn evaluate<T: Borrow<Self::Input>>(
+        parameters: &Self::Parameters,
+        input: T
+    ) -> Result<Self::Output, Error> {
+        poseidon::CRH::<F>::evaluate(parameters, pack_bytes::<F>(input.borrow()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prf::*;
+    use ark_std::test_rng;
+
+    type H = config::poseidon_bls12_377::Hash;
+
+    #[test]
+    fn test_poseidon_prf() {
+        let mut rng = test_rng();
+        let prf_params = JZPRFParams::<H>::trusted_setup(&mut rng);
+
+        let output = JZPRFInstance::<H>::new(&prf_params, JZPRFDomain::Address, &[0u8; 32], &[25u8; 32]).evaluate();
+        assert_eq!(output.len(), 32);
+
+        // a different key or input gives a different output
+        assert_ne!(output, JZPRFInstance::<H>::new(&prf_params, JZPRFDomain::Address, &[0u8; 32], &[26u8; 32]).evaluate());
+        assert_ne!(output, JZPRFInstance::<H>::new(&prf_params, JZPRFDomain::Address, &[1u8; 32], &[25u8; 32]).evaluate());
+        // and the length prefix keeps a trailing zero byte from colliding
+        assert_ne!(
+            JZPRFInstance::<H>::new(&prf_params, JZPRFDomain::Address, &[1u8], &[]).evaluate(),
+            JZPRFInstance::<H>::new(&prf_params, JZPRFDomain::Address, &[1u8, 0u8], &[]).evaluate()
+        );
+    }
+}
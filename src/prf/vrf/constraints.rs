@@ -0,0 +1,188 @@
+use ark_crypto_primitives::crh::{CRHSchemeGadget, poseidon::constraints::*};
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ec::twisted_edwards::TECurveConfig;
+use ark_ff::*;
+use ark_relations::r1cs::*;
+use ark_r1cs_std::prelude::*;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::groups::curves::twisted_edwards::AffineVar;
+use ark_std::{borrow::*, *};
+
+use super::{JZVRFParams, JZVRFProof, CHALLENGE_BITS};
+
+pub type PointVar<P, ConstraintF> = AffineVar<P, FpVar<ConstraintF>>;
+
+pub struct JZVRFParamsVar<P, ConstraintF>
+    where   P: TECurveConfig<BaseField = ConstraintF>,
+            ConstraintF: PrimeField + Absorb,
+{
+    pub generator: PointVar<P, ConstraintF>,
+    pub poseidon_params_var: CRHParametersVar<ConstraintF>,
+}
+
+impl<P, ConstraintF> AllocVar<JZVRFParams<P>, ConstraintF> for JZVRFParamsVar<P, ConstraintF>
+    where   P: TECurveConfig<BaseField = ConstraintF>,
+            ConstraintF: PrimeField + Absorb,
+{
+    fn new_variable<T: Borrow<JZVRFParams<P>>>(
+        cs: impl Into<Namespace<ConstraintF>>,
+        f: impl FnOnce() -> Result<T>,
+        mode: AllocationMode
+    ) -> Result<Self> {
+        f().and_then(|val| {
+            let cs = cs.into();
+            let params: &JZVRFParams<P> = val.borrow();
+
+            let generator = PointVar::<P, ConstraintF>::new_variable(
+                cs.clone(),
+                || Ok(params.generator),
+                mode
+            )?;
+
+            let poseidon_params_var = CRHParametersVar::<ConstraintF>::new_variable(
+                cs.clone(),
+                || Ok(&params.poseidon_params),
+                mode
+            )?;
+
+            Ok(JZVRFParamsVar { generator, poseidon_params_var })
+        })
+    }
+}
+
+/// the challenge and response are allocated as little-endian bits, which
+/// is what the scalar multiplications consume
+pub struct JZVRFProofVar<P, ConstraintF>
+    where   P: TECurveConfig<BaseField = ConstraintF>,
+            ConstraintF: PrimeField,
+{
+    pub gamma: PointVar<P, ConstraintF>,
+    pub challenge: Vec<Boolean<ConstraintF>>,
+    pub response: Vec<Boolean<ConstraintF>>,
+}
+
+impl<P, ConstraintF> AllocVar<JZVRFProof<P>, ConstraintF> for JZVRFProofVar<P, ConstraintF>
+    where   P: TECurveConfig<BaseField = ConstraintF>,
+            ConstraintF: PrimeField,
+{
+    fn new_variable<T: Borrow<JZVRFProof<P>>>(
+        cs: impl Into<Namespace<ConstraintF>>,
+        f: impl FnOnce() -> Result<T>,
+        mode: AllocationMode
+    ) -> Result<Self> {
+        f().and_then(|val| {
+            let cs = cs.into();
+            let proof: &JZVRFProof<P> = val.borrow();
+
+            let gamma = PointVar::<P, ConstraintF>::new_variable(
+                cs.clone(),
+                || Ok(proof.gamma),
+                mode
+            )?;
+
+            let challenge_bits = proof.challenge.into_bigint().to_bits_le();
+            let response_bits = proof.response.into_bigint().to_bits_le();
+            let scalar_bits = P::ScalarField::MODULUS_BIT_SIZE as usize;
+
+            let mut challenge = vec![];
+            for bit in challenge_bits.iter().take(CHALLENGE_BITS) {
+                challenge.push(Boolean::new_variable(cs.clone(), || Ok(bit), mode)?);
+            }
+
+            let mut response = vec![];
+            for bit in response_bits.iter().take(scalar_bits) {
+                response.push(Boolean::new_variable(cs.clone(), || Ok(bit), mode)?);
+            }
+
+            Ok(JZVRFProofVar { gamma, challenge, response })
+        })
+    }
+}
+
+fn poseidon<P, ConstraintF>(
+    params: &JZVRFParamsVar<P, ConstraintF>,
+    points: &[&PointVar<P, ConstraintF>]
+) -> Result<FpVar<ConstraintF>>
+    where   P: TECurveConfig<BaseField = ConstraintF>,
+            ConstraintF: PrimeField + Absorb,
+{
+    let coordinates: Vec<FpVar<ConstraintF>> = points
+        .iter()
+        .flat_map(|p| [p.x.clone(), p.y.clone()])
+        .collect();
+
+    CRHGadget::<ConstraintF>::evaluate(&params.poseidon_params_var, &coordinates)
+}
+
+/// Verifies the proof and enforces that `output` is the VRF output of pk
+/// on the input hashed to h. Hashing α to the curve is not done in-circuit:
+/// the verifier derives h = params.hash_to_curve(pk, α) and supplies it,
+/// typically as a public input next to pk.
+pub fn generate_constraints<P, ConstraintF>(
+    _cs: ConstraintSystemRef<ConstraintF>,
+    params: &JZVRFParamsVar<P, ConstraintF>,
+    pk: &PointVar<P, ConstraintF>,
+    h: &PointVar<P, ConstraintF>,
+    proof: &JZVRFProofVar<P, ConstraintF>,
+    output: &FpVar<ConstraintF>
+) -> Result<()>
+    where   P: TECurveConfig<BaseField = ConstraintF>,
+            ConstraintF: PrimeField + Absorb,
+{
+    if proof.challenge.len() != CHALLENGE_BITS {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+
+    // U = s·G - c·pk, V = s·H - c·Γ
+    let u = params.generator.scalar_mul_le(proof.response.iter())?
+        - pk.scalar_mul_le(proof.challenge.iter())?;
+    let v = h.scalar_mul_le(proof.response.iter())?
+        - proof.gamma.scalar_mul_le(proof.challenge.iter())?;
+
+    let digest = poseidon(params, &[pk, h, &proof.gamma, &u, &v])?;
+    let digest_bits = digest.to_bits_le()?;
+    Boolean::le_bits_to_fp_var(&digest_bits[..CHALLENGE_BITS])?
+        .enforce_equal(&Boolean::le_bits_to_fp_var(&proof.challenge)?)?;
+
+    poseidon(params, &[&proof.gamma])?.enforce_equal(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prf::vrf::*;
+    use ark_std::test_rng;
+
+    type P = crate::prf::config::ed_on_bls12_377::Curve;
+    type ConstraintF = ark_bls12_377::Fr;
+
+    #[test]
+    fn test_vrf_constraints() {
+        let mut rng = test_rng();
+        let params = JZVRFParams::<P>::trusted_setup(&mut rng);
+        let (sk, pk) = keygen(&params, &mut rng);
+        let (beta, proof) = prove(&params, &sk, b"round 1", &mut rng);
+        let h = params.hash_to_curve(&pk, b"round 1");
+
+        let cs = ConstraintSystem::<ConstraintF>::new_ref();
+        let params_var = JZVRFParamsVar::<P, ConstraintF>::new_constant(cs.clone(), &params).unwrap();
+        let pk_var = PointVar::<P, ConstraintF>::new_input(cs.clone(), || Ok(pk)).unwrap();
+        let h_var = PointVar::<P, ConstraintF>::new_input(cs.clone(), || Ok(h)).unwrap();
+        let proof_var = JZVRFProofVar::<P, ConstraintF>::new_witness(cs.clone(), || Ok(&proof)).unwrap();
+        let output_var = FpVar::<ConstraintF>::new_input(cs.clone(), || Ok(beta)).unwrap();
+
+        generate_constraints(cs.clone(), &params_var, &pk_var, &h_var, &proof_var, &output_var).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        // the proof is for another input
+        let cs = ConstraintSystem::<ConstraintF>::new_ref();
+        let params_var = JZVRFParamsVar::<P, ConstraintF>::new_constant(cs.clone(), &params).unwrap();
+        let pk_var = PointVar::<P, ConstraintF>::new_input(cs.clone(), || Ok(pk)).unwrap();
+        let h_var = PointVar::<P, ConstraintF>::new_input(cs.clone(), || Ok(params.hash_to_curve(&pk, b"round 2"))).unwrap();
+        let proof_var = JZVRFProofVar::<P, ConstraintF>::new_witness(cs.clone(), || Ok(&proof)).unwrap();
+        let output_var = FpVar::<ConstraintF>::new_input(cs.clone(), || Ok(beta)).unwrap();
+
+        generate_constraints(cs.clone(), &params_var, &pk_var, &h_var, &proof_var, &output_var).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+}
@@ -0,0 +1,204 @@
+pub mod constraints;
+
+use ark_crypto_primitives::crh::{CRHScheme, poseidon};
+use ark_crypto_primitives::sponge::{Absorb, poseidon::PoseidonConfig};
+use ark_ec::{*, twisted_edwards::{Projective, TECurveConfig}};
+use ark_ff::*;
+use ark_serialize::CanonicalSerialize;
+use ark_std::{*, rand::Rng};
+
+use crate::utils;
+
+pub const DEFAULT_DOMAIN: &str = "jzexe.prf.vrf";
+
+/// bits of the Fiat-Shamir challenge
+pub const CHALLENGE_BITS: usize = 128;
+
+/// EC-VRF over a twisted Edwards curve, in the style of RFC 9381: the
+/// output is determined by Γ = x·H(pk, α), and a Chaum-Pedersen proof shows
+/// that log_G(pk) = log_H(Γ). Challenges and outputs are Poseidon hashes over
+/// the curve's base field, which is the constraint field of the gadget, so
+/// a circuit can verify the proof without emulating a byte hash.
+#[derive(Derivative)]
+#[derivative(Clone(bound = "P: TECurveConfig"))]
+pub struct JZVRFParams<P: TECurveConfig>
+    where   P::BaseField: PrimeField + Absorb,
+{
+    pub domain: String,
+    pub generator: Projective<P>,
+    pub poseidon_params: PoseidonConfig<P::BaseField>,
+}
+
+impl<P: TECurveConfig> JZVRFParams<P>
+    where   P::BaseField: PrimeField + Absorb,
+{
+    pub fn from_domain(domain: &str) -> Self {
+        JZVRFParams {
+            domain: domain.to_string(),
+            generator: utils::hash_to_curve::<P>(domain.as_bytes(), 0),
+            poseidon_params: utils::poseidon_parameters::<P::BaseField>(2),
+        }
+    }
+
+    /// nothing is sampled, the rng is only here for parity with the PRF params
+    pub fn trusted_setup<R: Rng>(_rng: &mut R) -> Self {
+        Self::from_domain(DEFAULT_DOMAIN)
+    }
+
+    /// H(pk, α), hashed to the prime-order subgroup outside of any circuit
+    pub fn hash_to_curve(&self, pk: &Projective<P>, alpha: &[u8]) -> Projective<P> {
+        let mut message = vec![];
+        pk.serialize_compressed(&mut message).unwrap();
+        message.extend_from_slice(alpha);
+
+        utils::hash_bytes_to_curve::<P>(self.domain.as_bytes(), &message)
+    }
+}
+
+#[derive(Derivative)]
+#[derivative(Clone(bound = "P: TECurveConfig"))]
+pub struct JZVRFProof<P: TECurveConfig> {
+    /// Γ = x·H
+    pub gamma: Projective<P>,
+    /// c, below 2^CHALLENGE_BITS
+    pub challenge: P::ScalarField,
+    /// s = k + c·x
+    pub response: P::ScalarField,
+}
+
+/// (x, x·G)
+pub fn keygen<P: TECurveConfig, R: Rng>(
+    params: &JZVRFParams<P>,
+    rng: &mut R
+) -> (P::ScalarField, Projective<P>)
+    where   P::BaseField: PrimeField + Absorb,
+{
+    let sk = P::ScalarField::rand(rng);
+    (sk, params.generator.mul_bigint(sk.into_bigint()))
+}
+
+fn coordinates<P: TECurveConfig>(points: &[&Projective<P>]) -> Vec<P::BaseField> {
+    Projective::<P>::normalize_batch(&points.iter().map(|p| **p).collect::<Vec<_>>())
+        .iter()
+        .flat_map(|p| [p.x, p.y])
+        .collect()
+}
+
+/// the low CHALLENGE_BITS bits of Poseidon(pk, H, Γ, U, V)
+pub fn challenge<P: TECurveConfig>(
+    params: &JZVRFParams<P>,
+    points: &[&Projective<P>]
+) -> P::ScalarField
+    where   P::BaseField: PrimeField + Absorb,
+{
+    let digest = poseidon::CRH::<P::BaseField>::evaluate(
+        &params.poseidon_params,
+        coordinates::<P>(points)
+    ).unwrap();
+
+    let bits = digest.into_bigint().to_bits_le();
+    P::ScalarField::from_bigint(
+        <P::ScalarField as PrimeField>::BigInt::from_bits_le(&bits[..CHALLENGE_BITS])
+    ).unwrap()
+}
+
+/// the VRF output, Poseidon(Γ)
+pub fn output<P: TECurveConfig>(
+    params: &JZVRFParams<P>,
+    gamma: &Projective<P>
+) -> P::BaseField
+    where   P::BaseField: PrimeField + Absorb,
+{
+    poseidon::CRH::<P::BaseField>::evaluate(&params.poseidon_params, coordinates::<P>(&[gamma])).unwrap()
+}
+
+pub fn prove<P: TECurveConfig, R: Rng>(
+    params: &JZVRFParams<P>,
+    sk: &P::ScalarField,
+    alpha: &[u8],
+    rng: &mut R
+) -> (P::BaseField, JZVRFProof<P>)
+    where   P::BaseField: PrimeField + Absorb,
+{
+    let pk = params.generator.mul_bigint(sk.into_bigint());
+    let h = params.hash_to_curve(&pk, alpha);
+    let gamma = h.mul_bigint(sk.into_bigint());
+
+    let nonce = P::ScalarField::rand(rng);
+    let u = params.generator.mul_bigint(nonce.into_bigint());
+    let v = h.mul_bigint(nonce.into_bigint());
+
+    let c = challenge(params, &[&pk, &h, &gamma, &u, &v]);
+    let proof = JZVRFProof { gamma, challenge: c, response: nonce + c * sk };
+
+    (output(params, &gamma), proof)
+}
+
+/// the VRF output of α under pk, if the proof is valid
+pub fn verify<P: TECurveConfig>(
+    params: &JZVRFParams<P>,
+    pk: &Projective<P>,
+    alpha: &[u8],
+    proof: &JZVRFProof<P>
+) -> Option<P::BaseField>
+    where   P::BaseField: PrimeField + Absorb,
+{
+    let gamma = proof.gamma.into_affine();
+    if !gamma.is_on_curve() || !gamma.is_in_correct_subgroup_assuming_on_curve() {
+        return None;
+    }
+
+    // a small-order or identity key would make c·pk predictable, so the
+    // proof could be forged without a secret key
+    let key = pk.into_affine();
+    if key.is_zero() || !key.is_on_curve() || !key.is_in_correct_subgroup_assuming_on_curve() {
+        return None;
+    }
+
+    let h = params.hash_to_curve(pk, alpha);
+    let c = proof.challenge.into_bigint();
+    let s = proof.response.into_bigint();
+
+    // U = s·G - c·pk, V = s·H - c·Γ
+    let u = params.generator.mul_bigint(s) - pk.mul_bigint(c);
+    let v = h.mul_bigint(s) - proof.gamma.mul_bigint(c);
+
+    if challenge(params, &[pk, &h, &proof.gamma, &u, &v]) != proof.challenge {
+        return None;
+    }
+    Some(output(params, &proof.gamma))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::test_rng;
+
+    type P = crate::prf::config::ed_on_bls12_377::Curve;
+
+    #[test]
+    fn test_vrf() {
+        let mut rng = test_rng();
+        let params = JZVRFParams::<P>::trusted_setup(&mut rng);
+        let (sk, pk) = keygen(&params, &mut rng);
+
+        let (beta, proof) = prove(&params, &sk, b"round 1", &mut rng);
+        assert_eq!(verify(&params, &pk, b"round 1", &proof), Some(beta));
+        assert_eq!(verify(&params, &pk, b"round 2", &proof), None);
+
+        // the output is unique: a fresh proof carries the same output
+        let (beta_again, _) = prove(&params, &sk, b"round 1", &mut rng);
+        assert_eq!(beta, beta_again);
+
+        // someone else's key does not verify
+        let (_, other_pk) = keygen(&params, &mut rng);
+        assert_eq!(verify(&params, &other_pk, b"round 1", &proof), None);
+
+        // nor does the identity, or a key with a small-order component
+        assert_eq!(verify(&params, &Projective::<P>::zero(), b"round 1", &proof), None);
+        let torsion = twisted_edwards::Affine::<P>::new_unchecked(
+            <P as CurveConfig>::BaseField::zero(), -<P as CurveConfig>::BaseField::one()
+        );
+        assert_eq!(verify(&params, &(pk + torsion), b"round 1", &proof), None);
+    }
+}
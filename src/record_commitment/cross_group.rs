@@ -0,0 +1,379 @@
+use ark_ec::*;
+use ark_ec::models::bls12::*;
+use ark_ec::twisted_edwards::{Projective, TECurveConfig};
+use ark_ff::*;
+use ark_std::{*, rand::Rng};
+
+use crate::transcript::JZTranscript;
+use super::kzg::{JZRecord, JZKZGCommitmentParams, ScalarField};
+use super::pedersen_vector::{self, JZPedersenVectorParams};
+
+const DOMAIN: &[u8] = b"jzexe.record_commitment.cross_group";
+
+/// Proof that field `index` of a KZG record and the value behind an Edwards
+/// Pedersen commitment v·G + s·H are the same integer v < 2^bits.
+///
+/// The prover commits to every bit of v in both groups, A_j = b_j·L_index +
+/// r_j·L_0 and B_j = b_j·G + t_j·H, and shows with an OR proof that each
+/// pair hides the same bit. The weighted sums Σ 2^j·A_j and Σ 2^j·B_j then
+/// carry v in both groups; what remains of the record commitment is opened
+/// with a representation proof that avoids L_index, and what remains of the
+/// Pedersen commitment with a Schnorr proof on H. Challenges are 128-bit
+/// integers, so they embed in both scalar fields.
+#[derive(Derivative)]
+#[derivative(Clone(bound = "C: Bls12Config, P: TECurveConfig"))]
+pub struct JZCrossGroupEqualityProof<C: Bls12Config, P: TECurveConfig> {
+    pub kzg_bit_commitments: Vec<G1Projective<C>>,
+    pub pedersen_bit_commitments: Vec<Projective<P>>,
+    pub bit_proofs: Vec<JZBitProof<C, P>>,
+    /// R = Σ_{i != index} k_i·L_i
+    pub kzg_nonce_commitment: G1Projective<C>,
+    /// s_i = k_i + c·δ_i for every i != index, in index order
+    pub kzg_responses: Vec<ScalarField<C>>,
+    /// R' = k'·H
+    pub pedersen_nonce_commitment: Projective<P>,
+    pub pedersen_response: P::ScalarField,
+}
+
+/// CDS proof that (A, B) commits to 0 in both groups or to 1 in both groups;
+/// branch β shows A - β·L_index and B - β·G are multiples of L_0 and H
+#[derive(Derivative)]
+#[derivative(Clone(bound = "C: Bls12Config, P: TECurveConfig"))]
+pub struct JZBitProof<C: Bls12Config, P: TECurveConfig> {
+    /// c_0; c_1 = c - c_0 mod 2^128
+    pub challenge_0: u128,
+    pub kzg_nonce_commitments: [G1Projective<C>; 2],
+    pub pedersen_nonce_commitments: [Projective<P>; 2],
+    pub kzg_responses: [ScalarField<C>; 2],
+    pub pedersen_responses: [P::ScalarField; 2],
+}
+
+/// a 128-bit challenge as a scalar of either group
+fn embed<F: PrimeField>(c: u128) -> F {
+    F::from(c)
+}
+
+fn mul<G: Group>(point: &G, scalar: &G::ScalarField) -> G {
+    point.mul_bigint(scalar.into_bigint())
+}
+
+/// binds the setup of both groups, so a proof is tied to one CRS and one
+/// pair of Pedersen generators
+fn statement<const N: usize, const M: usize, C: Bls12Config, P: TECurveConfig>(
+    crs: &JZKZGCommitmentParams<N, M, C>,
+    pedersen_params: &JZPedersenVectorParams<1, P>,
+    commitment: &G1Projective<C>,
+    pedersen_commitment: &Projective<P>,
+    index: usize,
+    bits: usize
+) -> JZTranscript
+    where   <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>
+{
+    let mut transcript = JZTranscript::new(DOMAIN);
+    transcript.append_message(b"crs", &crs.digest());
+    transcript.append_point(b"pedersen_generator", &pedersen_params.generators[0]);
+    transcript.append_point(b"pedersen_blinding_generator", &pedersen_params.blinding_generator);
+    transcript.append_u64(b"index", index as u64);
+    transcript.append_u64(b"bits", bits as u64);
+    transcript.append_point(b"commitment", commitment);
+    transcript.append_point(b"pedersen_commitment", pedersen_commitment);
+    transcript
+}
+
+/// the bit commitments and both branches' nonce commitments of one OR proof
+fn append_bit<C: Bls12Config, P: TECurveConfig>(
+    transcript: &mut JZTranscript,
+    a: &G1Projective<C>,
+    b: &Projective<P>,
+    proof: &JZBitProof<C, P>
+) {
+    transcript.append_point(b"kzg_bit_commitment", a);
+    transcript.append_point(b"pedersen_bit_commitment", b);
+    for branch in 0..2 {
+        transcript.append_point(b"kzg_nonce_commitment", &proof.kzg_nonce_commitments[branch]);
+        transcript.append_point(b"pedersen_nonce_commitment", &proof.pedersen_nonce_commitments[branch]);
+    }
+}
+
+/// Σ 2^j·points[j]
+fn weighted_sum<G: Group>(points: &[G]) -> G {
+    points.iter().rev().fold(G::zero(), |acc, point| acc.double() + point)
+}
+
+/// proves that field `index` of `record` is the value committed to by
+/// pedersen_vector::commit(pedersen_params, [v], pedersen_blind); panics if
+/// the field does not fit in `bits` bits
+pub fn prove_cross_group_equality<const N: usize, const M: usize, C, P, R>(
+    record: &JZRecord<N, M, C>,
+    index: usize,
+    pedersen_params: &JZPedersenVectorParams<1, P>,
+    pedersen_blind: &P::ScalarField,
+    bits: usize,
+    rng: &mut R
+) -> JZCrossGroupEqualityProof<C, P>
+    where   C: Bls12Config,
+            P: TECurveConfig,
+            R: Rng,
+            <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>
+{
+    assert!(index > 0 && index < N, "field index {} cannot be compared", index);
+    assert!(
+        bits < ScalarField::<C>::MODULUS_BIT_SIZE as usize && bits < P::ScalarField::MODULUS_BIT_SIZE as usize,
+        "{} bits do not fit in both scalar fields", bits
+    );
+
+    let value_bits = record.fields()[index].into_bigint().to_bits_le();
+    assert!(value_bits[bits..].iter().all(|b| !b), "field {} does not fit in {} bits", index, bits);
+    let value = P::ScalarField::from_bigint(
+        <P::ScalarField as PrimeField>::BigInt::from_bits_le(&value_bits[..bits])
+    ).unwrap();
+
+    let crs = &record.crs;
+    let (l_value, l_blind) = (crs.crs_lagrange[index], crs.crs_lagrange[0]);
+    let (g, h) = (pedersen_params.generators[0], pedersen_params.blinding_generator);
+
+    let commitment = record.blinded_commitment();
+    let pedersen_commitment = pedersen_vector::commit(pedersen_params, &[value], pedersen_blind);
+    let mut transcript = statement(crs, pedersen_params, &commitment, &pedersen_commitment, index, bits);
+
+    // bit commitments, with the real branch of each OR proof committed to
+    // and the other branch simulated under a random challenge
+    let mut kzg_blinds = vec![];
+    let mut pedersen_blinds = vec![];
+    let mut kzg_bit_commitments = vec![];
+    let mut pedersen_bit_commitments = vec![];
+    let mut nonces = vec![];
+    let mut bit_proofs = vec![];
+    for &bit in value_bits[..bits].iter() {
+        let (r, t) = (ScalarField::<C>::rand(rng), P::ScalarField::rand(rng));
+        let mut a = mul(&l_blind, &r);
+        let mut b = mul(&h, &t);
+        if bit {
+            a += l_value;
+            b += g;
+        }
+
+        let (u, w) = (ScalarField::<C>::rand(rng), P::ScalarField::rand(rng));
+        let fake = 1 - bit as usize;
+        let fake_challenge: u128 = rng.gen();
+        let fake_kzg_response = ScalarField::<C>::rand(rng);
+        let fake_pedersen_response = P::ScalarField::rand(rng);
+
+        let mut proof = JZBitProof {
+            challenge_0: fake_challenge,
+            kzg_nonce_commitments: [mul(&l_blind, &u); 2],
+            pedersen_nonce_commitments: [mul(&h, &w); 2],
+            kzg_responses: [fake_kzg_response; 2],
+            pedersen_responses: [fake_pedersen_response; 2],
+        };
+        let (a_shift, b_shift) = if fake == 1 { (a - l_value, b - g) } else { (a, b) };
+        proof.kzg_nonce_commitments[fake] = mul(&l_blind, &fake_kzg_response)
+            - mul(&a_shift, &embed::<ScalarField<C>>(fake_challenge));
+        proof.pedersen_nonce_commitments[fake] = mul(&h, &fake_pedersen_response)
+            - mul(&b_shift, &embed::<P::ScalarField>(fake_challenge));
+
+        append_bit(&mut transcript, &a, &b, &proof);
+
+        kzg_blinds.push(r);
+        pedersen_blinds.push(t);
+        kzg_bit_commitments.push(a);
+        pedersen_bit_commitments.push(b);
+        nonces.push((u, w, fake_challenge));
+        bit_proofs.push(proof);
+    }
+
+    // the record commitment minus Σ 2^j·A_j has no L_index component, and
+    // its L_0 coefficient absorbs the bit blinds
+    let mut deltas = record.blinded_fields();
+    deltas[0] -= kzg_blinds.iter().rev().fold(ScalarField::<C>::zero(), |acc, r| acc.double() + r);
+    let kzg_nonces: Vec<ScalarField<C>> = (0..N).map(|_| ScalarField::<C>::rand(rng)).collect();
+    let mut kzg_nonce_commitment = G1Projective::<C>::zero();
+    for i in (0..N).filter(|&i| i != index) {
+        kzg_nonce_commitment += mul(&crs.crs_lagrange[i], &kzg_nonces[i]);
+    }
+
+    let pedersen_delta = *pedersen_blind
+        - pedersen_blinds.iter().rev().fold(P::ScalarField::zero(), |acc, t| acc.double() + t);
+    let pedersen_nonce = P::ScalarField::rand(rng);
+    let pedersen_nonce_commitment = mul(&h, &pedersen_nonce);
+
+    transcript.append_point(b"kzg_nonce_commitment", &kzg_nonce_commitment);
+    transcript.append_point(b"pedersen_nonce_commitment", &pedersen_nonce_commitment);
+    let c = transcript.challenge_u128(b"challenge");
+
+    for ((proof, &bit), ((r, t), (u, w, fake_challenge))) in bit_proofs.iter_mut()
+        .zip(value_bits[..bits].iter())
+        .zip(kzg_blinds.iter().zip(pedersen_blinds.iter()).zip(nonces))
+    {
+        let real = bit as usize;
+        let real_challenge = c.wrapping_sub(fake_challenge);
+        proof.challenge_0 = if real == 0 { real_challenge } else { fake_challenge };
+        proof.kzg_responses[real] = u + embed::<ScalarField<C>>(real_challenge) * r;
+        proof.pedersen_responses[real] = w + embed::<P::ScalarField>(real_challenge) * t;
+    }
+
+    let c_kzg = embed::<ScalarField<C>>(c);
+    let kzg_responses = (0..N)
+        .filter(|&i| i != index)
+        .map(|i| kzg_nonces[i] + c_kzg * deltas[i])
+        .collect();
+    let pedersen_response = pedersen_nonce + embed::<P::ScalarField>(c) * pedersen_delta;
+
+    JZCrossGroupEqualityProof {
+        kzg_bit_commitments,
+        pedersen_bit_commitments,
+        bit_proofs,
+        kzg_nonce_commitment,
+        kzg_responses,
+        pedersen_nonce_commitment,
+        pedersen_response,
+    }
+}
+
+pub fn verify_cross_group_equality<const N: usize, const M: usize, C, P>(
+    crs: &JZKZGCommitmentParams<N, M, C>,
+    commitment: &G1Projective<C>,
+    index: usize,
+    pedersen_params: &JZPedersenVectorParams<1, P>,
+    pedersen_commitment: &Projective<P>,
+    bits: usize,
+    proof: &JZCrossGroupEqualityProof<C, P>
+) -> bool
+    where   C: Bls12Config,
+            P: TECurveConfig,
+            <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>
+{
+    if index == 0 || index >= N
+        || bits >= ScalarField::<C>::MODULUS_BIT_SIZE as usize
+        || bits >= P::ScalarField::MODULUS_BIT_SIZE as usize
+        || proof.kzg_bit_commitments.len() != bits
+        || proof.pedersen_bit_commitments.len() != bits
+        || proof.bit_proofs.len() != bits
+        || proof.kzg_responses.len() != N - 1
+    {
+        return false;
+    }
+
+    // a small-order component would shift the Edwards side of the proof
+    // without touching the prime-order part the KZG side is compared with
+    let in_subgroup = |point: &Projective<P>| point.into_affine().is_in_correct_subgroup_assuming_on_curve();
+    if !in_subgroup(pedersen_commitment) || !proof.pedersen_bit_commitments.iter().all(in_subgroup) {
+        return false;
+    }
+
+    let (l_value, l_blind) = (crs.crs_lagrange[index], crs.crs_lagrange[0]);
+    let (g, h) = (pedersen_params.generators[0], pedersen_params.blinding_generator);
+
+    let mut transcript = statement(crs, pedersen_params, commitment, pedersen_commitment, index, bits);
+    for ((a, b), bit_proof) in proof.kzg_bit_commitments.iter()
+        .zip(proof.pedersen_bit_commitments.iter())
+        .zip(proof.bit_proofs.iter())
+    {
+        append_bit(&mut transcript, a, b, bit_proof);
+    }
+    transcript.append_point(b"kzg_nonce_commitment", &proof.kzg_nonce_commitment);
+    transcript.append_point(b"pedersen_nonce_commitment", &proof.pedersen_nonce_commitment);
+    let c = transcript.challenge_u128(b"challenge");
+
+    // every pair of bit commitments hides the same bit
+    for ((a, b), bit_proof) in proof.kzg_bit_commitments.iter()
+        .zip(proof.pedersen_bit_commitments.iter())
+        .zip(proof.bit_proofs.iter())
+    {
+        let challenges = [bit_proof.challenge_0, c.wrapping_sub(bit_proof.challenge_0)];
+        let shifts = [(*a, *b), (*a - l_value, *b - g)];
+        for branch in 0..2 {
+            let (a_shift, b_shift) = shifts[branch];
+            if mul(&l_blind, &bit_proof.kzg_responses[branch])
+                != bit_proof.kzg_nonce_commitments[branch]
+                    + mul(&a_shift, &embed::<ScalarField<C>>(challenges[branch]))
+            {
+                return false;
+            }
+            if mul(&h, &bit_proof.pedersen_responses[branch])
+                != bit_proof.pedersen_nonce_commitments[branch]
+                    + mul(&b_shift, &embed::<P::ScalarField>(challenges[branch]))
+            {
+                return false;
+            }
+        }
+    }
+
+    // what remains of the record commitment avoids L_index
+    let kzg_rest = *commitment - weighted_sum(&proof.kzg_bit_commitments);
+    let mut lhs = G1Projective::<C>::zero();
+    let generators = (0..N).filter(|&i| i != index).map(|i| crs.crs_lagrange[i]);
+    for (generator, response) in generators.zip(proof.kzg_responses.iter()) {
+        lhs += mul(&generator, response);
+    }
+    if lhs != proof.kzg_nonce_commitment + mul(&kzg_rest, &embed::<ScalarField<C>>(c)) {
+        return false;
+    }
+
+    // and what remains of the Pedersen commitment is a multiple of H
+    let pedersen_rest = *pedersen_commitment - weighted_sum(&proof.pedersen_bit_commitments);
+    mul(&h, &proof.pedersen_response)
+        == proof.pedersen_nonce_commitment + mul(&pedersen_rest, &embed::<P::ScalarField>(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::test_rng;
+
+    type C = ark_bls12_377::Config;
+    type P = ark_ed_on_bls12_377::EdwardsConfig;
+    const AMOUNT: usize = 3;
+
+    #[test]
+    fn test_cross_group_equality() {
+        let mut rng = test_rng();
+        let crs = JZKZGCommitmentParams::<4, 4, C>::trusted_setup(&mut rng);
+        let pedersen_params = JZPedersenVectorParams::<1, P>::from_domain(pedersen_vector::DEFAULT_DOMAIN);
+
+        let record = JZRecord::<4, 4, C>::new(
+            &crs, &[vec![1u8; 24], vec![2u8; 31], vec![40u8], vec![25u8, 1u8]], &vec![9u8; 24]
+        );
+        let commitment = record.blinded_commitment();
+
+        let pedersen_blind = <P as CurveConfig>::ScalarField::rand(&mut rng);
+        let amount = <P as CurveConfig>::ScalarField::from(256u64 + 25);
+        let pedersen_commitment = pedersen_vector::commit(&pedersen_params, &[amount], &pedersen_blind);
+
+        let proof = prove_cross_group_equality(&record, AMOUNT, &pedersen_params, &pedersen_blind, 64, &mut rng);
+        assert!(verify_cross_group_equality(
+            &crs, &commitment, AMOUNT, &pedersen_params, &pedersen_commitment, 64, &proof
+        ));
+
+        // a Pedersen commitment to another amount does not verify
+        let other = pedersen_vector::commit(&pedersen_params, &[amount + amount], &pedersen_blind);
+        assert!(!verify_cross_group_equality(
+            &crs, &commitment, AMOUNT, &pedersen_params, &other, 64, &proof
+        ));
+
+        // nor does the proof transfer to another field of the record
+        assert!(!verify_cross_group_equality(
+            &crs, &commitment, AMOUNT - 1, &pedersen_params, &pedersen_commitment, 64, &proof
+        ));
+
+        // nor to another setup
+        let other_crs = JZKZGCommitmentParams::<4, 4, C>::trusted_setup(&mut rng);
+        assert!(!verify_cross_group_equality(
+            &other_crs, &commitment, AMOUNT, &pedersen_params, &pedersen_commitment, 64, &proof
+        ));
+
+        // commitments outside the prime-order subgroup are rejected
+        let torsion = twisted_edwards::Affine::<P>::new_unchecked(
+            <P as CurveConfig>::BaseField::zero(), -<P as CurveConfig>::BaseField::one()
+        );
+        let shifted = pedersen_commitment + torsion;
+        assert!(!verify_cross_group_equality(
+            &crs, &commitment, AMOUNT, &pedersen_params, &shifted, 64, &proof
+        ));
+        let mut tampered = proof.clone();
+        tampered.pedersen_bit_commitments[0] += torsion;
+        assert!(!verify_cross_group_equality(
+            &crs, &commitment, AMOUNT, &pedersen_params, &pedersen_commitment, 64, &tampered
+        ));
+    }
+}
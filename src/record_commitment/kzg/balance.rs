@@ -0,0 +1,174 @@
+use ark_ec::*;
+use ark_ec::models::bls12::*;
+use ark_ff::*;
+use ark_std::{*, rand::Rng};
+
+use crate::transcript::JZTranscript;
+use super::{JZRecord, JZKZGCommitmentParams, ScalarField};
+
+/// Proof that the blinded commitments of some input and output records
+/// carry the same total in the amount field.
+///
+/// The difference D = Σ inputs - Σ outputs is a combination of the Lagrange
+/// CRS elements, and its coefficient on L_amount is the net amount. The
+/// proof is a multi-base Schnorr proof of knowledge of D's coefficients on
+/// every other L_i. It shows the net amount is zero only while L_amount is
+/// not a known combination of the other L_i, i.e. while nobody knows tau:
+/// with tau, anyone can forge a proof for any amount, so soundness needs a
+/// setup whose tau was discarded (see JZKZGUniversalParams::trusted_setup).
+/// The challenge binds the CRS digest, so a proof does not carry over to
+/// other parameters. Amounts add up modulo the scalar field, so callers
+/// must still keep them range-checked (e.g. inside the circuit) to rule
+/// out wrap-around.
+#[derive(Derivative)]
+#[derivative(Clone(bound = "C: Bls12Config"))]
+pub struct JZBalanceProof<C: Bls12Config> {
+    /// R = Σ_{i != amount} k_i·L_i
+    pub nonce_commitment: G1Projective<C>,
+    /// s_i = k_i + c·δ_i for every i != amount, in index order
+    pub responses: Vec<ScalarField<C>>,
+}
+
+fn difference<C: Bls12Config>(
+    inputs: &[G1Projective<C>],
+    outputs: &[G1Projective<C>]
+) -> G1Projective<C> {
+    inputs.iter().sum::<G1Projective<C>>() - outputs.iter().sum::<G1Projective<C>>()
+}
+
+fn challenge<const N: usize, const M: usize, C: Bls12Config>(
+    crs: &JZKZGCommitmentParams<N, M, C>,
+    difference: &G1Projective<C>,
+    nonce_commitment: &G1Projective<C>,
+    amount_index: usize
+) -> ScalarField<C>
+    where <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>
+{
+    let mut transcript = JZTranscript::new(b"jzexe.kzg.balance");
+    transcript.append_message(b"crs", &crs.digest());
+    transcript.append_u64(b"amount_index", amount_index as u64);
+    transcript.append_point(b"difference", difference);
+    transcript.append_point(b"nonce_commitment", nonce_commitment);
+
+    transcript.challenge_scalar(b"challenge")
+}
+
+/// proves that Σ inputs and Σ outputs agree on the amount field of their
+/// blinded commitments; panics if they do not
+pub fn prove_balance<const N: usize, const M: usize, C: Bls12Config, R: Rng>(
+    inputs: &[JZRecord<N, M, C>],
+    outputs: &[JZRecord<N, M, C>],
+    amount_index: usize,
+    rng: &mut R
+) -> JZBalanceProof<C>
+    where <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>
+{
+    assert!(amount_index < N, "amount index {} out of bounds", amount_index);
+    let crs = &inputs.first().or(outputs.first()).expect("no records").crs;
+
+    // δ_i = Σ inputs_i - Σ outputs_i, over the blinded fields
+    let mut deltas = [ScalarField::<C>::zero(); N];
+    for record in inputs.iter() {
+        for (delta, field) in deltas.iter_mut().zip(record.blinded_fields().iter()) {
+            *delta += field;
+        }
+    }
+    for record in outputs.iter() {
+        for (delta, field) in deltas.iter_mut().zip(record.blinded_fields().iter()) {
+            *delta -= field;
+        }
+    }
+    assert!(deltas[amount_index].is_zero(), "input and output amounts do not balance");
+
+    let nonces: Vec<ScalarField<C>> = (0..N)
+        .map(|_| ScalarField::<C>::rand(rng))
+        .collect();
+
+    let mut nonce_commitment = G1Projective::<C>::zero();
+    for i in (0..N).filter(|&i| i != amount_index) {
+        nonce_commitment += crs.crs_lagrange[i].mul_bigint(nonces[i].into_bigint());
+    }
+
+    let input_coms: Vec<G1Projective<C>> = inputs.iter().map(|r| r.blinded_commitment()).collect();
+    let output_coms: Vec<G1Projective<C>> = outputs.iter().map(|r| r.blinded_commitment()).collect();
+    let c = challenge(crs, &difference::<C>(&input_coms, &output_coms), &nonce_commitment, amount_index);
+
+    let responses = (0..N)
+        .filter(|&i| i != amount_index)
+        .map(|i| nonces[i] + c * deltas[i])
+        .collect();
+
+    JZBalanceProof { nonce_commitment, responses }
+}
+
+/// checks Σ_{i != amount} s_i·L_i = R + c·(Σ inputs - Σ outputs)
+pub fn verify_balance<const N: usize, const M: usize, C: Bls12Config>(
+    crs: &JZKZGCommitmentParams<N, M, C>,
+    input_commitments: &[G1Projective<C>],
+    output_commitments: &[G1Projective<C>],
+    amount_index: usize,
+    proof: &JZBalanceProof<C>
+) -> bool
+    where <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>
+{
+    if amount_index >= N || proof.responses.len() != N - 1 {
+        return false;
+    }
+
+    let diff = difference::<C>(input_commitments, output_commitments);
+    let c = challenge(crs, &diff, &proof.nonce_commitment, amount_index);
+
+    let mut lhs = G1Projective::<C>::zero();
+    let generators = (0..N).filter(|&i| i != amount_index).map(|i| crs.crs_lagrange[i]);
+    for (generator, response) in generators.zip(proof.responses.iter()) {
+        lhs += generator.mul_bigint(response.into_bigint());
+    }
+
+    lhs == proof.nonce_commitment + diff.mul_bigint(c.into_bigint())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::test_rng;
+
+    type C = ark_bls12_377::Config;
+    const AMOUNT: usize = 3;
+
+    fn coin(
+        crs: &JZKZGCommitmentParams<8, 4, C>,
+        owner: u8,
+        amount: u8,
+        blind: u8
+    ) -> JZRecord<8, 4, C> {
+        let fields: [Vec<u8>; 8] = [
+            vec![owner ^ amount; 24], vec![owner; 31], vec![40u8], vec![amount],
+            vec![0u8], vec![0u8], vec![0u8], vec![amount; 31],
+        ];
+        JZRecord::<8, 4, C>::new(crs, &fields, &vec![blind; 24])
+    }
+
+    #[test]
+    fn test_balance_proof() {
+        let mut rng = test_rng();
+        let crs = JZKZGCommitmentParams::<8, 4, C>::trusted_setup(&mut rng);
+
+        let inputs = [coin(&crs, 1, 30, 5), coin(&crs, 1, 12, 6)];
+        let outputs = [coin(&crs, 2, 40, 7), coin(&crs, 1, 2, 8)];
+
+        let input_coms: Vec<_> = inputs.iter().map(|r| r.blinded_commitment()).collect();
+        let output_coms: Vec<_> = outputs.iter().map(|r| r.blinded_commitment()).collect();
+
+        let proof = prove_balance(&inputs, &outputs, AMOUNT, &mut rng);
+        assert!(verify_balance(&crs, &input_coms, &output_coms, AMOUNT, &proof));
+
+        // the proof does not transfer to commitments with a different net amount
+        let inflated = [coin(&crs, 2, 41, 7), coin(&crs, 1, 2, 8)];
+        let inflated_coms: Vec<_> = inflated.iter().map(|r| r.blinded_commitment()).collect();
+        assert!(!verify_balance(&crs, &input_coms, &inflated_coms, AMOUNT, &proof));
+
+        // nor to another setup
+        let other_crs = JZKZGCommitmentParams::<8, 4, C>::trusted_setup(&mut rng);
+        assert!(!verify_balance(&other_crs, &input_coms, &output_coms, AMOUNT, &proof));
+    }
+}
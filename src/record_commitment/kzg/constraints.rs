@@ -4,9 +4,15 @@ use std::ops::AddAssign;
 use ark_relations::r1cs::*;
 use ark_r1cs_std::{bits::uint8::UInt8, prelude::*, alloc::AllocVar};
 use ark_r1cs_std::groups::curves::short_weierstrass::bls12::*;
-use ark_ec::{models::bls12::*, bls12::Bls12Config, CurveConfig};
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::pairing::{PairingVar as PG, bls12::PairingVar};
+use ark_ec::{models::bls12::*, bls12::Bls12Config, CurveConfig, CurveGroup, Group};
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
 
-use super::{JZRecord, JZKZGCommitmentParams};
+use super::{JZRecord, JZDynRecord, JZKZGCommitmentParams, JZKZGDynCommitmentParams, JZKZGOpening, ScalarField};
+use crate::codec::{JZEndianness, constraints::enforce_canonical};
+use crate::record_commitment::schema::constraints::JZRecordFieldsVar;
+use crate::record_commitment::packing::{JZPackingLayout, constraints::unpack_field_vars};
 
 pub struct JZKZGCommitmentParamsVar<const N: usize, C: Bls12Config> {
     pub crs: Vec<G1Var<C>>,
@@ -119,6 +125,121 @@ impl<const N: usize, const M: usize, C, ConstraintF> AllocVar<JZRecord<N, M, C>,
     }
 }
 
+pub struct JZKZGDynCommitmentParamsVar<C: Bls12Config> {
+    pub crs: Vec<G1Var<C>>,
+}
+
+impl<const M: usize, C: Bls12Config, ConstraintF: Field>
+    AllocVar<JZKZGDynCommitmentParams<M, C>, ConstraintF> for JZKZGDynCommitmentParamsVar<C>
+    where   C: Bls12Config<Fp = ConstraintF>,
+            <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>,
+            ConstraintF: PrimeField,
+{
+    fn new_variable<T: Borrow<JZKZGDynCommitmentParams<M, C>>>(
+        cs: impl Into<Namespace<ConstraintF>>,
+        f: impl FnOnce() -> Result<T>,
+        mode: AllocationMode
+    ) -> Result<Self> {
+        f().and_then(|val| {
+            let cs = cs.into();
+            let mut crs_vars: Vec<G1Var<C>> = vec![];
+            for crs_i in val.borrow().crs_lagrange.iter() {
+                crs_vars.push(G1Var::<C>::new_variable(cs.clone(), || Ok(*crs_i), mode)?);
+            }
+
+            Ok(JZKZGDynCommitmentParamsVar { crs: crs_vars })
+        })
+    }
+}
+
+pub struct JZDynRecordVar<C, ConstraintF>
+    where   C: Bls12Config<Fp = ConstraintF>,
+            ConstraintF: PrimeField,
+{
+    pub fields: Vec<Vec<UInt8<ConstraintF>>>,
+    pub blind: Vec<UInt8<ConstraintF>>,
+    pub commitment: G1Var<C>,
+    pub blinded_commitment: G1Var<C>
+}
+
+impl<const M: usize, C, ConstraintF> AllocVar<JZDynRecord<M, C>, ConstraintF> for JZDynRecordVar<C, ConstraintF>
+    where   C: Bls12Config<Fp = ConstraintF>,
+            ConstraintF: PrimeField,
+            <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>
+{
+    fn new_variable<T: Borrow<JZDynRecord<M, C>>>(
+        cs: impl Into<Namespace<ConstraintF>>,
+        f: impl FnOnce() -> Result<T>,
+        mode: AllocationMode
+    ) -> Result<Self> {
+        f().and_then(|val| {
+            let cs = cs.into();
+            let record: &JZDynRecord<M, C> = val.borrow();
+
+            let mut fields = vec![];
+            for field in record.fields.iter() {
+                let mut field_byte_vars = vec![];
+                for byte in field.iter() {
+                    field_byte_vars.push(UInt8::<ConstraintF>::new_variable(
+                        cs.clone(), || Ok(byte), mode
+                    )?);
+                }
+                fields.push(field_byte_vars);
+            }
+
+            let mut blind = vec![];
+            for byte in record.blind.iter() {
+                blind.push(UInt8::<ConstraintF>::new_variable(
+                    cs.clone(), || Ok(byte), mode
+                )?);
+            }
+
+            let commitment = G1Var::<C>::new_variable(
+                cs.clone(),
+                || Ok(record.commitment()),
+                mode
+            )?;
+
+            let blinded_commitment = G1Var::<C>::new_variable(
+                cs.clone(),
+                || Ok(record.blinded_commitment()),
+                mode
+            )?;
+
+            Ok(JZDynRecordVar { fields, blind, commitment, blinded_commitment })
+        })
+    }
+}
+
+impl<const N: usize, C, ConstraintF> JZRecordVar<N, C, ConstraintF>
+    where   C: Bls12Config<Fp = ConstraintF>,
+            ConstraintF: PrimeField,
+{
+    /// the logical fields of a record whose slots were packed with `layout`
+    pub fn unpacked_fields(&self, layout: &JZPackingLayout) -> Result<Vec<Vec<UInt8<ConstraintF>>>> {
+        unpack_field_vars(layout, &self.fields)
+    }
+}
+
+impl<C, ConstraintF> JZDynRecordVar<C, ConstraintF>
+    where   C: Bls12Config<Fp = ConstraintF>,
+            ConstraintF: PrimeField,
+{
+    /// the logical fields of a record whose slots were packed with `layout`
+    pub fn unpacked_fields(&self, layout: &JZPackingLayout) -> Result<Vec<Vec<UInt8<ConstraintF>>>> {
+        unpack_field_vars(layout, &self.fields)
+    }
+}
+
+impl<const N: usize, C, ConstraintF> JZRecordFieldsVar<N, ConstraintF> for JZRecordVar<N, C, ConstraintF>
+    where   C: Bls12Config<Fp = ConstraintF>,
+            ConstraintF: PrimeField,
+{
+    fn field_vars(&self) -> &[Vec<UInt8<ConstraintF>>; N] {
+        &self.fields
+    }
+}
+
 pub fn generate_constraints<const N: usize, C, ConstraintF>(
     cs: ConstraintSystemRef<ConstraintF>,
     params: &JZKZGCommitmentParamsVar<N, C>,
@@ -127,32 +248,439 @@ pub fn generate_constraints<const N: usize, C, ConstraintF>(
 where   C: Bls12Config<Fp = ConstraintF>,
         ConstraintF: PrimeField,
 {
+    enforce_commitments::<C, ConstraintF>(
+        cs,
+        &params.crs[..N],
+        &record.fields,
+        &record.blind,
+        &record.commitment,
+        &record.blinded_commitment
+    )
+}
 
-    let mut aggregate_var = G1Var::<C>::new_witness(
-        ark_relations::ns!(cs, "aggregate_pk"), 
-        || Ok(G1Projective::<C>::zero())
-    )?;
+/// same as generate_constraints, for a record whose number of fields is
+/// only known at runtime; fails if the record does not have `num_fields`
+/// fields, has none, or the CRS has fewer slots
+pub fn generate_dyn_constraints<C, ConstraintF>(
+    cs: ConstraintSystemRef<ConstraintF>,
+    params: &JZKZGDynCommitmentParamsVar<C>,
+    record: &JZDynRecordVar<C, ConstraintF>,
+    num_fields: usize
+) -> Result<()>
+where   C: Bls12Config<Fp = ConstraintF>,
+        ConstraintF: PrimeField,
+{
+    if num_fields == 0 || record.fields.len() != num_fields || num_fields > params.crs.len() {
+        return Err(SynthesisError::Unsatisfiable);
+    }
 
-    for i in 0..N {        
-        let crs_i: &G1Var<C> = &params.crs[i];
-        let elem_i: &Vec<UInt8<ConstraintF>> = &record.fields[i];
+    enforce_commitments::<C, ConstraintF>(
+        cs,
+        &params.crs[..num_fields],
+        &record.fields,
+        &record.blind,
+        &record.commitment,
+        &record.blinded_commitment
+    )
+}
 
-        let crs_i_pow_elem_i: G1Var<C> = crs_i.scalar_mul_le(
-            elem_i.to_bits_le()?.iter())?;
+fn enforce_commitments<C, ConstraintF>(
+    _cs: ConstraintSystemRef<ConstraintF>,
+    crs: &[G1Var<C>],
+    fields: &[Vec<UInt8<ConstraintF>>],
+    blind: &[UInt8<ConstraintF>],
+    commitment: &G1Var<C>,
+    blinded_commitment: &G1Var<C>
+) -> Result<()>
+where   C: Bls12Config<Fp = ConstraintF>,
+        ConstraintF: PrimeField,
+{
+
+    let mut aggregate_var = G1Var::<C>::zero();
+
+    for (crs_i, elem_i) in crs.iter().zip(fields.iter()) {
+        let crs_i_pow_elem_i: G1Var<C> = scalar_mul_le::<C, ConstraintF>(crs_i, &elem_i.to_bits_le()?)?;
 
         aggregate_var.add_assign(crs_i_pow_elem_i);
     }
 
-    record.commitment.enforce_equal(&aggregate_var)?;
+    commitment.enforce_equal(&aggregate_var)?;
 
     //blinded commitment constraints
-    let crs_0 = &params.crs[0];
-    let crs_0_pow_blind: G1Var<C> = crs_0.scalar_mul_le(
-        record.blind.to_bits_le()?.iter())?;
+    let crs_0 = &crs[0];
+    let crs_0_pow_blind: G1Var<C> = scalar_mul_le::<C, ConstraintF>(crs_0, &blind.to_bits_le()?)?;
     
     aggregate_var.add_assign(crs_0_pow_blind);
 
-    record.blinded_commitment.enforce_equal(&aggregate_var)?;
+    blinded_commitment.enforce_equal(&aggregate_var)?;
+
+    Ok(())
+}
+
+/// width of the lookup windows in fixed_base_scalar_mul_le
+pub const FIXED_BASE_WINDOW: usize = 3;
+
+/// every window digit k is looked up as (k + DIGIT_OFFSET)·2^(3j)·B, which
+/// keeps each partial sum strictly below the next window's multiple of B;
+/// the offsets are subtracted again at the end
+const DIGIT_OFFSET: u64 = 2;
+
+/// bits·base, with fixed-base lookups when the base is a constant (e.g. a
+/// CRS allocated with new_constant) and scalar_mul_le otherwise
+pub fn scalar_mul_le<C, ConstraintF>(
+    base: &G1Var<C>,
+    bits: &[Boolean<ConstraintF>]
+) -> Result<G1Var<C>>
+where   C: Bls12Config<Fp = ConstraintF>,
+        ConstraintF: PrimeField,
+{
+    if base.is_constant() {
+        fixed_base_scalar_mul_le::<C, ConstraintF>(&base.value()?, bits)
+    } else {
+        base.scalar_mul_le(bits.iter())
+    }
+}
+
+/// bits·base for a constant base. The bits are consumed in windows of
+/// FIXED_BASE_WINDOW bits, each selecting one of 2^w precomputed multiples
+/// of the base through a multilinear polynomial in the bits (2^w - w - 1
+/// constraints), which is then added with incomplete affine addition (3
+/// constraints). The digit offsets rule out the exceptional cases as long
+/// as the partial sums stay below the group order, so the bits are split
+/// into chunks that are combined with complete addition. Committing to an
+/// 8-field record (test_kzg_com_constraint_count) takes 3295 constraints,
+/// against 6807 for double-and-add on the same constant CRS and 12335 for
+/// a CRS allocated as a witness.
+pub fn fixed_base_scalar_mul_le<C, ConstraintF>(
+    base: &G1Projective<C>,
+    bits: &[Boolean<ConstraintF>]
+) -> Result<G1Var<C>>
+where   C: Bls12Config<Fp = ConstraintF>,
+        ConstraintF: PrimeField,
+{
+    if base.is_zero() || bits.iter().all(|bit| bit.is_constant()) {
+        let mut product = G1Projective::<C>::zero();
+        for bit in bits.iter().rev() {
+            product.double_in_place();
+            if bit.value()? {
+                product += base;
+            }
+        }
+        return Ok(G1Var::<C>::constant(product));
+    }
+
+    let scalar_bits = <<C::G1Config as CurveConfig>::ScalarField as PrimeField>::MODULUS_BIT_SIZE as usize;
+    let chunk_len = ((scalar_bits - 2) / FIXED_BASE_WINDOW) * FIXED_BASE_WINDOW;
+
+    let mut result = G1Var::<C>::zero();
+    let mut chunk_base = *base;
+    for chunk in bits.chunks(chunk_len) {
+        result += fixed_base_chunk::<C, ConstraintF>(&chunk_base, chunk)?;
+        for _ in 0..chunk_len {
+            chunk_base.double_in_place();
+        }
+    }
+    Ok(result)
+}
+
+/// the partial sums of a chunk are s_j = Σ_{i<j} d_i·2^(3i)·B with digits
+/// d_i in [2, 9], so s_j < 2·2^(3j)·B <= d_j·2^(3j)·B and the whole chunk
+/// stays below (9/7)·2^chunk_len < r; s_j and the next summand can then
+/// never be equal or opposite
+fn fixed_base_chunk<C, ConstraintF>(
+    base: &G1Projective<C>,
+    bits: &[Boolean<ConstraintF>]
+) -> Result<G1Var<C>>
+where   C: Bls12Config<Fp = ConstraintF>,
+        ConstraintF: PrimeField,
+{
+    let mut acc: Option<(FpVar<ConstraintF>, FpVar<ConstraintF>)> = None;
+    let mut offsets = G1Projective::<C>::zero();
+    let mut window_base = *base;
+
+    for window in bits.chunks(FIXED_BASE_WINDOW) {
+        let offset = window_base.mul_bigint([DIGIT_OFFSET]);
+        offsets += offset;
+
+        // table[k] = (k + DIGIT_OFFSET)·window_base
+        let mut table = Vec::with_capacity(1 << window.len());
+        let mut entry = offset;
+        for _ in 0..(1 << window.len()) {
+            table.push(entry);
+            entry += window_base;
+        }
+        let table = G1Projective::<C>::normalize_batch(&table);
+
+        let (x, y) = lookup::<C, ConstraintF>(window, &table)?;
+        acc = Some(match acc {
+            None => (x, y),
+            Some((acc_x, acc_y)) => add_incomplete(&acc_x, &acc_y, &x, &y)?,
+        });
+
+        for _ in 0..window.len() {
+            window_base.double_in_place();
+        }
+    }
+
+    let (x, y) = acc.unwrap();
+    Ok(G1Var::<C>::new(x, y, FpVar::one()) - offsets)
+}
+
+/// the affine coordinates of table[k], for k the little-endian value of
+/// bits, as multilinear polynomials in the bits
+fn lookup<C, ConstraintF>(
+    bits: &[Boolean<ConstraintF>],
+    table: &[G1Affine<C>]
+) -> Result<(FpVar<ConstraintF>, FpVar<ConstraintF>)>
+where   C: Bls12Config<Fp = ConstraintF>,
+        ConstraintF: PrimeField,
+{
+    // monomials[S] = Π_{i in S} bits[i], for every subset S as a bitmask
+    let mut monomials = vec![FpVar::<ConstraintF>::one()];
+    for bit in bits.iter() {
+        let bit = FpVar::from(bit.clone());
+        for s in 0..monomials.len() {
+            let monomial = &monomials[s] * &bit;
+            monomials.push(monomial);
+        }
+    }
+
+    let mut xs: Vec<ConstraintF> = table.iter().map(|p| p.x).collect();
+    let mut ys: Vec<ConstraintF> = table.iter().map(|p| p.y).collect();
+    // Möbius transform from values on the hypercube to monomial coefficients
+    for i in 0..bits.len() {
+        for s in 0..table.len() {
+            if s & (1 << i) != 0 {
+                xs[s] = xs[s] - xs[s ^ (1 << i)];
+                ys[s] = ys[s] - ys[s ^ (1 << i)];
+            }
+        }
+    }
+
+    let mut x = FpVar::<ConstraintF>::zero();
+    let mut y = FpVar::<ConstraintF>::zero();
+    for ((monomial, x_coeff), y_coeff) in monomials.iter().zip(xs).zip(ys) {
+        x += monomial * x_coeff;
+        y += monomial * y_coeff;
+    }
+    Ok((x, y))
+}
+
+/// (x1, y1) + (x2, y2) with the incomplete affine formula, which requires x1 != x2
+fn add_incomplete<ConstraintF: PrimeField>(
+    x1: &FpVar<ConstraintF>,
+    y1: &FpVar<ConstraintF>,
+    x2: &FpVar<ConstraintF>,
+    y2: &FpVar<ConstraintF>
+) -> Result<(FpVar<ConstraintF>, FpVar<ConstraintF>)> {
+    let cs = x1.cs().or(y1.cs()).or(x2.cs()).or(y2.cs());
+    if cs.is_none() {
+        let lambda = (y2.value()? - y1.value()?) / (x2.value()? - x1.value()?);
+        let x3 = lambda.square() - x1.value()? - x2.value()?;
+        let y3 = lambda * (x1.value()? - x3) - y1.value()?;
+        return Ok((FpVar::constant(x3), FpVar::constant(y3)));
+    }
+
+    let lambda = FpVar::new_witness(ark_relations::ns!(cs, "lambda"), || {
+        let dx = x2.value()? - x1.value()?;
+        Ok((y2.value()? - y1.value()?) * dx.inverse().ok_or(SynthesisError::DivisionByZero)?)
+    })?;
+    lambda.mul_equals(&(x2 - x1), &(y2 - y1))?;
+
+    let x3 = FpVar::new_witness(ark_relations::ns!(cs, "x3"), || {
+        Ok(lambda.value()?.square() - x1.value()? - x2.value()?)
+    })?;
+    lambda.mul_equals(&lambda, &(&x3 + x1 + x2))?;
+
+    let y3 = FpVar::new_witness(ark_relations::ns!(cs, "y3"), || {
+        Ok(lambda.value()? * (x1.value()? - x3.value()?) - y1.value()?)
+    })?;
+    lambda.mul_equals(&(x1 - &x3), &(&y3 + y1))?;
+
+    Ok((x3, y3))
+}
+
+/// enforces rerandomized = commitment + blind·L_0, i.e. that a public
+/// re-randomized commitment opens to the same record as `commitment`
+/// (typically the tree leaf) under the private blind
+pub fn generate_rerandomization_constraints<const N: usize, C, ConstraintF>(
+    _cs: ConstraintSystemRef<ConstraintF>,
+    params: &JZKZGCommitmentParamsVar<N, C>,
+    commitment: &G1Var<C>,
+    rerandomized: &G1Var<C>,
+    blind: &[UInt8<ConstraintF>]
+) -> Result<()>
+where   C: Bls12Config<Fp = ConstraintF>,
+        ConstraintF: PrimeField,
+{
+    let crs_0_pow_blind: G1Var<C> = scalar_mul_le::<C, ConstraintF>(&params.crs[0], &blind.to_bits_le()?)?;
+
+    rerandomized.enforce_equal(&(commitment.clone() + crs_0_pow_blind))?;
+
+    Ok(())
+}
+
+pub struct JZKZGVerifierKeyVar<C, ConstraintF>
+    where   C: Bls12Config<Fp = ConstraintF>,
+            ConstraintF: PrimeField,
+{
+    /// generator of G1
+    pub g: G1Var<C>,
+    /// generator of G2
+    pub h: G2PreparedVar<C>,
+    /// τ·H
+    pub tau_h: G2PreparedVar<C>,
+    /// little-endian bits of the domain elements ω^j, for j in [N]
+    pub domain: Vec<Vec<Boolean<ConstraintF>>>,
+}
+
+impl<const N: usize, const M: usize, C, ConstraintF>
+    AllocVar<JZKZGCommitmentParams<N, M, C>, ConstraintF> for JZKZGVerifierKeyVar<C, ConstraintF>
+    where   C: Bls12Config<Fp = ConstraintF>,
+            <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>,
+            ConstraintF: PrimeField,
+{
+    fn new_variable<T: Borrow<JZKZGCommitmentParams<N, M, C>>>(
+        cs: impl Into<Namespace<ConstraintF>>,
+        f: impl FnOnce() -> Result<T>,
+        mode: AllocationMode
+    ) -> Result<Self> {
+        f().and_then(|val| {
+            let cs = cs.into();
+            let crs: &JZKZGCommitmentParams<N, M, C> = val.borrow();
+
+            let g = G1Var::<C>::new_variable(
+                cs.clone(),
+                || Ok(crs.crs_coefficient_g1[0]),
+                mode
+            )?;
+
+            let h = G2PreparedVar::<C>::new_variable(
+                cs.clone(),
+                || Ok(G2Prepared::<C>::from(crs.crs_coefficient_g2[0].into_affine())),
+                mode
+            )?;
+
+            let tau_h = G2PreparedVar::<C>::new_variable(
+                cs.clone(),
+                || Ok(G2Prepared::<C>::from(crs.crs_coefficient_g2[1].into_affine())),
+                mode
+            )?;
+
+            let radix2_domain = Radix2EvaluationDomain::<
+                <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField
+            >::new(N).unwrap();
+
+            let mut domain = vec![];
+            for j in 0..N {
+                let bits = radix2_domain.element(j).into_bigint().to_bits_le();
+                let mut bit_vars = vec![];
+                for bit in bits {
+                    bit_vars.push(Boolean::new_variable(cs.clone(), || Ok(bit), mode)?);
+                }
+                domain.push(bit_vars);
+            }
+
+            Ok(JZKZGVerifierKeyVar { g, h, tau_h, domain })
+        })
+    }
+}
+
+pub struct JZKZGOpeningVar<C, ConstraintF>
+    where   C: Bls12Config<Fp = ConstraintF>,
+            ConstraintF: PrimeField,
+{
+    /// index of the opened field, as an element of the constraint field
+    pub index: FpVar<ConstraintF>,
+    /// little-endian bytes of the opened value
+    pub value: Vec<UInt8<ConstraintF>>,
+    pub proof: G1Var<C>,
+}
+
+impl<C, ConstraintF> AllocVar<JZKZGOpening<C>, ConstraintF> for JZKZGOpeningVar<C, ConstraintF>
+    where   C: Bls12Config<Fp = ConstraintF>,
+            ConstraintF: PrimeField,
+{
+    fn new_variable<T: Borrow<JZKZGOpening<C>>>(
+        cs: impl Into<Namespace<ConstraintF>>,
+        f: impl FnOnce() -> Result<T>,
+        mode: AllocationMode
+    ) -> Result<Self> {
+        f().and_then(|val| {
+            let cs = cs.into();
+            let opening: &JZKZGOpening<C> = val.borrow();
+
+            let index = FpVar::<ConstraintF>::new_variable(
+                cs.clone(),
+                || Ok(ConstraintF::from(opening.index as u64)),
+                mode
+            )?;
+
+            let mut value = vec![];
+            for byte in opening.value.into_bigint().to_bytes_le() {
+                value.push(UInt8::<ConstraintF>::new_variable(
+                    cs.clone(),
+                    || Ok(byte),
+                    mode
+                )?);
+            }
+
+            let proof = G1Var::<C>::new_variable(
+                cs.clone(),
+                || Ok(opening.proof),
+                mode
+            )?;
+
+            Ok(JZKZGOpeningVar { index, value, proof })
+        })
+    }
+}
+
+/// enforces that `commitment` opens to `opening.value` at ω^`opening.index`.
+/// The index selects ω^i among the domain elements in the verifier key, so it
+/// may be a private witness; it is constrained to lie in [N]. The value is
+/// constrained below the scalar field's modulus, so v and v + r cannot both
+/// pass as openings.
+pub fn generate_opening_constraints<C, ConstraintF>(
+    _cs: ConstraintSystemRef<ConstraintF>,
+    vk: &JZKZGVerifierKeyVar<C, ConstraintF>,
+    commitment: &G1Var<C>,
+    opening: &JZKZGOpeningVar<C, ConstraintF>
+) -> Result<()>
+where   C: Bls12Config<Fp = ConstraintF>,
+        ConstraintF: PrimeField,
+{
+    // select the bits of ω^index, one equality flag per domain element
+    let mut point_bits = vk.domain[0].clone();
+    let mut num_matches = FpVar::<ConstraintF>::zero();
+    for (j, domain_bits) in vk.domain.iter().enumerate() {
+        let is_j = opening.index.is_eq(&FpVar::constant(ConstraintF::from(j as u64)))?;
+        num_matches += FpVar::from(is_j.clone());
+
+        for (bit, domain_bit) in point_bits.iter_mut().zip(domain_bits.iter()) {
+            *bit = Boolean::conditionally_select(&is_j, domain_bit, bit)?;
+        }
+    }
+    // index must be one of 0..N
+    num_matches.enforce_equal(&FpVar::one())?;
+
+    enforce_canonical::<ScalarField<C>, ConstraintF>(&opening.value, JZEndianness::Little)?;
+    let value_g = vk.g.scalar_mul_le(opening.value.to_bits_le()?.iter())?;
+    let point_proof = opening.proof.scalar_mul_le(point_bits.iter())?;
+
+    // e(C - v·G + ω^i·π, H) · e(-π, τ·H) = 1
+    let lhs = commitment.clone() - value_g + point_proof;
+    let neg_proof = opening.proof.negate()?;
+
+    let pairing_product = PairingVar::<C>::product_of_pairings(
+        &[
+            G1PreparedVar::<C>::from_group_var(&lhs)?,
+            G1PreparedVar::<C>::from_group_var(&neg_proof)?,
+        ],
+        &[vk.h.clone(), vk.tau_h.clone()]
+    )?;
+
+    pairing_product.enforce_equal(&<PairingVar<C> as PG<Bls12<C>>>::GTVar::one())?;
 
     Ok(())
 }
@@ -162,6 +690,8 @@ mod tests {
     use super::*;
     use ark_std::test_rng;
     use rand::RngCore;
+    use ark_std::UniformRand;
+    use crate::utils;
 
     #[test]
     fn test_kzg_com() {
@@ -197,4 +727,216 @@ mod tests {
         generate_constraints(cs.clone(), &crs_var, &coin_var).unwrap();
         assert!(cs.is_satisfied().unwrap(), "constraints not satisfied");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_kzg_dyn_com() {
+        let mut rng = test_rng();
+        let srs = super::super::JZKZGUniversalParams::<4, ark_bls12_377::Config>::trusted_setup(8, &mut rng);
+
+        // five fields fit in the 8-slot CRS; the remaining slots commit to zero
+        let crs = JZKZGDynCommitmentParams::from_universal(&srs, 5).unwrap();
+        assert_eq!(crs.size(), 8);
+        assert_eq!(
+            JZKZGDynCommitmentParams::from_universal(&srs, 9).err(),
+            Some(crate::record_commitment::schema::JZSchemaError::TooManyFields { capacity: 8, actual: 9 })
+        );
+        let mut wrapped = vec![vec![20u8; 24], ark_bls12_377::Fr::MODULUS.to_bytes_le()];
+        assert_eq!(
+            JZDynRecord::new(&crs, &wrapped, &vec![9u8; 24]).err(),
+            Some(crate::record_commitment::schema::JZSchemaError::NonCanonical { field: "field" })
+        );
+        wrapped.pop();
+        assert_eq!(
+            JZDynRecord::new(&crs, &wrapped, &ark_bls12_377::Fr::MODULUS.to_bytes_le()).err(),
+            Some(crate::record_commitment::schema::JZSchemaError::NonCanonical { field: "blind" })
+        );
+        let fields = vec![vec![20u8; 24], vec![2u8; 31], vec![40u8], vec![10u8], vec![7u8, 1u8]];
+        let coin = JZDynRecord::new(&crs, &fields, &vec![9u8; 24]).unwrap();
+
+        let mut padded = fields.clone();
+        padded.resize(8, vec![]);
+        let fixed = JZRecord::<8, 4, ark_bls12_377::Config>::new(
+            &srs.specialize::<8>(), &padded.try_into().unwrap(), &vec![9u8; 24]
+        );
+        assert_eq!(coin.blinded_commitment(), fixed.blinded_commitment());
+
+        assert!(JZDynRecord::new(&crs, &vec![vec![1u8]; 9], &vec![9u8; 24]).is_err());
+        assert!(JZDynRecord::new(&crs, &[], &vec![9u8; 24]).is_err());
+
+        let cs = ConstraintSystem::<ark_bw6_761::Fr>::new_ref();
+        let crs_var = JZKZGDynCommitmentParamsVar::<ark_bls12_377::Config>::new_constant(cs.clone(), &crs).unwrap();
+        let coin_var = JZDynRecordVar::<ark_bls12_377::Config, ark_bls12_377::Fq>::new_witness(cs.clone(), || Ok(&coin)).unwrap();
+
+        assert!(generate_dyn_constraints(cs.clone(), &crs_var, &coin_var, 4).is_err());
+        generate_dyn_constraints(cs.clone(), &crs_var, &coin_var, 5).unwrap();
+        assert!(cs.is_satisfied().unwrap(), "constraints not satisfied");
+
+        // a record without fields has no slot to blind
+        let empty = JZDynRecord { crs: crs.clone(), fields: vec![], blind: vec![9u8; 24] };
+        let empty_var = JZDynRecordVar::<ark_bls12_377::Config, ark_bls12_377::Fq>::new_witness(cs.clone(), || Ok(&empty)).unwrap();
+        assert!(generate_dyn_constraints(cs.clone(), &crs_var, &empty_var, 0).is_err());
+    }
+
+    #[test]
+    fn test_fixed_base_scalar_mul() {
+        let mut rng = test_rng();
+        let base = G1Projective::<ark_bls12_377::Config>::rand(&mut rng);
+
+        let mut scalars = vec![vec![0u8; 32], vec![255u8; 32], vec![1u8], vec![7u8, 0u8, 1u8]];
+        let mut random = [0u8; 40];
+        rng.fill_bytes(&mut random);
+        scalars.push(random.to_vec());
+
+        for scalar in scalars.iter() {
+            let cs = ConstraintSystem::<ark_bw6_761::Fr>::new_ref();
+            let bits = UInt8::new_witness_vec(cs.clone(), scalar).unwrap().to_bits_le().unwrap();
+            let product = fixed_base_scalar_mul_le::<ark_bls12_377::Config, _>(&base, &bits).unwrap();
+
+            let expected = base.mul_bigint(BigInt::<6>::from_bits_le(&utils::bytes_to_bits(scalar)));
+            assert_eq!(product.value().unwrap(), expected);
+            assert!(cs.is_satisfied().unwrap(), "constraints not satisfied");
+        }
+    }
+
+    #[test]
+    fn test_kzg_com_constraint_count() {
+        type C = ark_bls12_377::Config;
+        let mut rng = test_rng();
+        let crs = JZKZGCommitmentParams::<8, 4, C>::trusted_setup(&mut rng);
+
+        let fields: [Vec<u8>; 8] = [
+            vec![1u8; 31], vec![7u8; 32], vec![40u8; 8], vec![10u8; 8],
+            vec![0u8; 8], vec![0u8; 8], vec![0u8; 8], vec![9u8; 32],
+        ];
+        let coin = JZRecord::<8, 4, C>::new(&crs, &fields, &vec![3u8; 31]);
+
+        // fixed-base lookups against the constant CRS
+        let cs = ConstraintSystem::<ark_bw6_761::Fr>::new_ref();
+        let crs_var = JZKZGCommitmentParamsVar::<8, C>::new_constant(cs.clone(), &crs).unwrap();
+        let coin_var = JZRecordVar::<8, C, ark_bls12_377::Fq>::new_witness(cs.clone(), || Ok(&coin)).unwrap();
+        let before = cs.num_constraints();
+        generate_constraints(cs.clone(), &crs_var, &coin_var).unwrap();
+        let fixed_base = cs.num_constraints() - before;
+        assert!(cs.is_satisfied().unwrap(), "constraints not satisfied");
+
+        // the previous per-bit double-and-add on the same constant CRS
+        let before = cs.num_constraints();
+        let mut aggregate = G1Var::<C>::zero();
+        for (crs_i, field) in crs_var.crs.iter().zip(coin_var.fields.iter()) {
+            aggregate += crs_i.scalar_mul_le(field.to_bits_le().unwrap().iter()).unwrap();
+        }
+        aggregate += crs_var.crs[0].scalar_mul_le(coin_var.blind.to_bits_le().unwrap().iter()).unwrap();
+        aggregate.enforce_equal(&coin_var.blinded_commitment).unwrap();
+        let double_and_add = cs.num_constraints() - before;
+
+        // variable-base fallback, for a CRS allocated as a witness
+        let crs_var = JZKZGCommitmentParamsVar::<8, C>::new_witness(cs.clone(), || Ok(&crs)).unwrap();
+        let before = cs.num_constraints();
+        generate_constraints(cs.clone(), &crs_var, &coin_var).unwrap();
+        let variable_base = cs.num_constraints() - before;
+        assert!(cs.is_satisfied().unwrap(), "constraints not satisfied");
+
+        assert_eq!((fixed_base, double_and_add, variable_base), (3295, 6807, 12335));
+    }
+
+    #[test]
+    fn test_kzg_opening() {
+        let mut rng = test_rng();
+        let crs = JZKZGCommitmentParams::<4, 4, ark_bls12_377::Config>::trusted_setup(&mut rng);
+
+        let records: [Vec<u8>; 4] = [
+            vec![20u8, 30u8],
+            vec![7u8; 31],
+            vec![40u8],
+            vec![40u8, 50u8, 60u8, 70u8]
+        ];
+
+        let coin = JZRecord::<4, 4, ark_bls12_377::Config>::new(&crs, &records, &vec![3u8; 24]);
+        let opening = coin.blinded_opening(2);
+
+        let cs = ConstraintSystem::<ark_bw6_761::Fr>::new_ref();
+
+        let vk_var = JZKZGVerifierKeyVar::<ark_bls12_377::Config, ark_bls12_377::Fq>::new_constant(
+            cs.clone(), &crs
+        ).unwrap();
+
+        let com_var = G1Var::<ark_bls12_377::Config>::new_input(
+            cs.clone(), || Ok(coin.blinded_commitment())
+        ).unwrap();
+
+        // the index is public, the value stays private
+        let opening_var = JZKZGOpeningVar::<ark_bls12_377::Config, ark_bls12_377::Fq>::new_witness(
+            cs.clone(), || Ok(&opening)
+        ).unwrap();
+        let index_var = FpVar::new_input(
+            cs.clone(), || Ok(ark_bls12_377::Fq::from(2u64))
+        ).unwrap();
+        opening_var.index.enforce_equal(&index_var).unwrap();
+
+        generate_opening_constraints(cs.clone(), &vk_var, &com_var, &opening_var).unwrap();
+        assert!(cs.is_satisfied().unwrap(), "constraints not satisfied");
+
+        // opening at the wrong index must fail
+        let mut bad_opening = opening.clone();
+        bad_opening.index = 1;
+
+        let cs = ConstraintSystem::<ark_bw6_761::Fr>::new_ref();
+        let vk_var = JZKZGVerifierKeyVar::<ark_bls12_377::Config, ark_bls12_377::Fq>::new_constant(
+            cs.clone(), &crs
+        ).unwrap();
+        let com_var = G1Var::<ark_bls12_377::Config>::new_input(
+            cs.clone(), || Ok(coin.blinded_commitment())
+        ).unwrap();
+        let opening_var = JZKZGOpeningVar::<ark_bls12_377::Config, ark_bls12_377::Fq>::new_witness(
+            cs.clone(), || Ok(&bad_opening)
+        ).unwrap();
+
+        generate_opening_constraints(cs.clone(), &vk_var, &com_var, &opening_var).unwrap();
+        assert!(!cs.is_satisfied().unwrap(), "wrong index accepted");
+
+        // so must a wrong value, and the non-canonical encoding of v + r
+        // that scalar_mul_le alone would read as the same scalar
+        let wrong = (opening.value + ark_bls12_377::Fr::from(1u64)).into_bigint().to_bytes_le();
+        let mut shifted = opening.value.into_bigint();
+        shifted.add_with_carry(&ark_bls12_377::Fr::MODULUS);
+        for (value, message) in [(wrong, "wrong value accepted"), (shifted.to_bytes_le(), "non-canonical value accepted")] {
+            let cs = ConstraintSystem::<ark_bw6_761::Fr>::new_ref();
+            let vk_var = JZKZGVerifierKeyVar::<ark_bls12_377::Config, ark_bls12_377::Fq>::new_constant(
+                cs.clone(), &crs
+            ).unwrap();
+            let com_var = G1Var::<ark_bls12_377::Config>::new_input(
+                cs.clone(), || Ok(coin.blinded_commitment())
+            ).unwrap();
+            let mut opening_var = JZKZGOpeningVar::<ark_bls12_377::Config, ark_bls12_377::Fq>::new_witness(
+                cs.clone(), || Ok(&opening)
+            ).unwrap();
+            opening_var.value = UInt8::new_witness_vec(cs.clone(), &value).unwrap();
+
+            generate_opening_constraints(cs.clone(), &vk_var, &com_var, &opening_var).unwrap();
+            assert!(!cs.is_satisfied().unwrap(), "{}", message);
+        }
+    }
+
+    #[test]
+    fn test_kzg_rerandomization() {
+        let mut rng = test_rng();
+        let crs = JZKZGCommitmentParams::<4, 4, ark_bls12_377::Config>::trusted_setup(&mut rng);
+
+        let records: [Vec<u8>; 4] = [vec![20u8, 30u8], vec![7u8; 31], vec![40u8], vec![10u8]];
+        let coin = JZRecord::<4, 4, ark_bls12_377::Config>::new(&crs, &records, &vec![3u8; 24]);
+        let (rerandomized, _) = coin.rerandomize(&mut rng);
+
+        let cs = ConstraintSystem::<ark_bw6_761::Fr>::new_ref();
+        let crs_var = JZKZGCommitmentParamsVar::<4, ark_bls12_377::Config>::new_constant(cs.clone(), &crs).unwrap();
+
+        // the leaf stays private, only the fresh commitment is public
+        let leaf_var = G1Var::<ark_bls12_377::Config>::new_witness(cs.clone(), || Ok(coin.commitment())).unwrap();
+        let public_var = G1Var::<ark_bls12_377::Config>::new_input(
+            cs.clone(), || Ok(rerandomized.blinded_commitment())
+        ).unwrap();
+        let blind_var = UInt8::new_witness_vec(cs.clone(), &rerandomized.blind).unwrap();
+
+        generate_rerandomization_constraints(cs.clone(), &crs_var, &leaf_var, &public_var, &blind_var).unwrap();
+        assert!(cs.is_satisfied().unwrap(), "constraints not satisfied");
+    }
+}
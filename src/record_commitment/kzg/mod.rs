@@ -1,14 +1,25 @@
 pub mod constraints;
+pub mod balance;
+pub mod rerandomize;
+pub mod sigma;
 
 use ark_ec::*;
 use ark_std::{*, rand::Rng};
 use ark_std::borrow::*;
 use ark_std::convert::*;
 use ark_ff::*;
-use ark_poly::Polynomial;
+use ark_poly::{
+    DenseUVPolynomial,
+    univariate::DensePolynomial,
+    EvaluationDomain,
+    Radix2EvaluationDomain,
+    Evaluations
+};
 use ark_ec::models::bls12::*;
+use ark_ec::pairing::Pairing;
 
-use crate::utils;
+use crate::record_commitment::schema::*;
+use crate::transcript::JZTranscript;
 
 //#[derive(Clone)]
 #[derive(Derivative)]
@@ -24,19 +35,66 @@ pub struct JZKZGCommitmentParams<const N: usize, const M: usize, C: Bls12Config>
     pub crs_lagrange: Vec<G1Projective<C>>,
 }
 
-type ScalarField<P> = <<P as Bls12Config>::G1Config as CurveConfig>::ScalarField;
+pub type ScalarField<P> = <<P as Bls12Config>::G1Config as CurveConfig>::ScalarField;
 
 impl<const N: usize, const M: usize, C: Bls12Config> JZKZGCommitmentParams<N, M, C>
     where <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>,
 {
 
-    pub fn trusted_setup<R: Rng>(_rng: &mut R) -> Self {  
-        let tau: ScalarField<C> = ScalarField::<C>::from(BigInt::<M>::from(42 as u32));
+    pub fn trusted_setup<R: Rng>(rng: &mut R) -> Self {
+        JZKZGUniversalParams::<M, C>::trusted_setup(N, rng).specialize::<N>()
+    }
+
+    /// hash of every CRS element; proofs absorb it into their transcript,
+    /// so a proof made under one setup does not verify under another
+    pub fn digest(&self) -> [u8; 32] {
+        let mut transcript = JZTranscript::new(b"jzexe.kzg.crs");
+        for point in self.crs_coefficient_g1.iter().chain(self.crs_lagrange.iter()) {
+            transcript.append_point(b"g1", point);
+        }
+        for point in self.crs_coefficient_g2.iter() {
+            transcript.append_point(b"g2", point);
+        }
+
+        let mut digest = [0u8; 32];
+        transcript.challenge_bytes(b"digest", &mut digest);
+        digest
+    }
+}
+
+/// Powers-of-tau SRS shared by records of every arity up to `max_size`;
+/// the Lagrange-basis CRS for any power-of-two domain is derived from the
+/// powers alone, so deriving it needs no knowledge of tau.
+///
+/// Binding of every commitment, and the soundness of the sigma and balance
+/// proofs over them, rests on nobody knowing tau: whoever does can open a
+/// commitment to any value.
+#[derive(Derivative)]
+#[derivative(Clone(bound = "C: Bls12Config"))]
+pub struct JZKZGUniversalParams<const M: usize, C: Bls12Config>
+    where <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>
+{
+    /// [τ^i]G for i < 4·max_size
+    pub powers_of_tau_g1: Vec<G1Projective<C>>,
+    /// [τ^i]H for i < 4·max_size
+    pub powers_of_tau_g2: Vec<G2Projective<C>>,
+}
+
+impl<const M: usize, C: Bls12Config> JZKZGUniversalParams<M, C>
+    where <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>,
+{
+    /// single-party setup that samples tau from `rng` and drops it once the
+    /// powers are built. Whoever runs it, or can replay `rng`, learns tau, so
+    /// it is only fit for tests; deployments need the powers from a
+    /// multi-party powers-of-tau ceremony.
+    pub fn trusted_setup<R: Rng>(max_size: usize, rng: &mut R) -> Self {
+        assert!(max_size.is_power_of_two(), "max size {} is not a power of two", max_size);
+        let tau = ScalarField::<C>::rand(rng);
 
         let g = G1Projective::<C>::generator();
         let h = G2Projective::<C>::generator();
 
-        let crs_coefficient_g1 = (0..4*N)
+        let powers_of_tau_g1 = (0..4*max_size)
             .map(|i| g.mul_bigint(
                 tau.pow(
                     &[i as u64]
@@ -44,7 +102,7 @@ impl<const N: usize, const M: usize, C: Bls12Config> JZKZGCommitmentParams<N, M,
             ))
             .collect();
 
-        let crs_coefficient_g2 = (0..4*N)
+        let powers_of_tau_g2 = (0..4*max_size)
             .map(|i| h.mul_bigint(
                 tau.pow(
                     &[i as u64]
@@ -52,15 +110,38 @@ impl<const N: usize, const M: usize, C: Bls12Config> JZKZGCommitmentParams<N, M,
             ))
             .collect();
 
-        let crs_lagrange = (0..N)
-            .map(|i| g.mul_bigint(
-                utils::lagrange_poly(N, i)
-                .evaluate(&tau)
-                .into_bigint()
-                ))
-            .collect();
+        JZKZGUniversalParams { powers_of_tau_g1, powers_of_tau_g2 }
+    }
+
+    pub fn max_size(&self) -> usize {
+        self.powers_of_tau_g1.len() / 4
+    }
+
+    /// [L_i(τ)]G over the domain of size n: since L_i(τ) = (1/n)·Σ_j ω^{-ij}·τ^j,
+    /// this is the inverse FFT of the first n powers of tau, done in the group
+    pub fn lagrange_crs(&self, n: usize) -> Vec<G1Projective<C>> {
+        assert!(
+            n.is_power_of_two() && n <= self.max_size(),
+            "domain size {} is not a power of two up to {}", n, self.max_size()
+        );
+
+        let domain = Radix2EvaluationDomain::<ScalarField<C>>::new(n).unwrap();
+        domain.ifft(&self.powers_of_tau_g1[..n])
+    }
 
-        JZKZGCommitmentParams { crs_coefficient_g1, crs_coefficient_g2, crs_lagrange }
+    /// the CRS for records with N fields; panics unless N is a power of two
+    /// up to max_size
+    pub fn specialize<const N: usize>(&self) -> JZKZGCommitmentParams<N, M, C> {
+        assert!(
+            N.is_power_of_two() && N <= self.max_size(),
+            "record size {} is not a power of two up to {}", N, self.max_size()
+        );
+
+        JZKZGCommitmentParams {
+            crs_coefficient_g1: self.powers_of_tau_g1[..4*N].to_vec(),
+            crs_coefficient_g2: self.powers_of_tau_g2[..4*N].to_vec(),
+            crs_lagrange: self.lagrange_crs(N),
+        }
     }
 }
 
@@ -90,58 +171,423 @@ impl<const N: usize, const M: usize, C: Bls12Config> JZRecord<N, M, C>
         }
     }
 
+    /// builds a record from a typed schema value, checking the encoding is canonical
+    pub fn from_schema<S: JZRecordSchema<N>>(
+        crs: &JZKZGCommitmentParams<N, M, C>,
+        value: &S,
+        blind: &Vec<u8>
+    ) -> Result<Self, JZSchemaError> {
+        let fields = value.encode::<ScalarField<C>>()?;
+        Ok(Self::new(crs, &fields, blind))
+    }
+
+    pub fn to_schema<S: JZRecordSchema<N>>(&self) -> Result<S, JZSchemaError> {
+        S::decode::<ScalarField<C>>(&self.fields)
+    }
+
     pub fn commitment(&self) -> G1Projective<C> {
-        let mut acc = G1Projective::<C>::zero();
-        for (i, field) in self.fields.iter().enumerate() {
-            if i < N {
-                let crs_elem = self.crs.crs_lagrange[i];
-                let exp = BigInt::<M>::from_bits_le(
-                    utils::bytes_to_bits(&field).as_slice()
-                );
-                
-                acc += crs_elem.clone().mul_bigint(exp);
-            }
-        }
-        acc
+        lagrange_commitment::<C>(&self.crs.crs_lagrange, &self.fields)
     }
 
     pub fn blinded_commitment(&self) -> G1Projective<C> {
-        let com = self.commitment();
+        self.commitment() + blind_commitment::<C>(&self.crs.crs_lagrange, &self.blind)
+    }
 
-        let blind_bi = BigInt::<M>::from_bits_le(
-            utils::bytes_to_bits(&self.blind).as_slice()
-        );
-        let blind_group_elem = self.crs
-            .crs_lagrange[0]
-            .clone()
-            .mul_bigint(blind_bi);
+    /// each field as a scalar; panics unless they are canonical, see schema::to_field
+    pub fn fields(&self) -> [ScalarField<C>; N] {
+        field_elements::<C>(&self.fields).try_into().unwrap()
+    }
 
-        com + blind_group_elem
+    pub fn blinded_fields(&self) -> [ScalarField<C>; N] {
+        let mut fields = self.fields();
+        fields[0] += bytes_to_scalar::<C>(&self.blind);
+        fields
     }
 
-    pub fn fields(&self) -> [ScalarField<C>; N] {
-        let mut fields = [ScalarField::<C>::zero(); N];
-        for (i, field) in self.fields.iter().enumerate() {
-            fields[i] = ScalarField::<C>::from(
-                BigInt::<M>::from_bits_le(
-                    utils::bytes_to_bits(&field).as_slice()
-                )
-            );
+    /// KZG opening of commitment() at the evaluation point ω^index
+    pub fn opening(&self, index: usize) -> JZKZGOpening<C> {
+        open_fields(&self.crs, &self.fields(), index)
+    }
+
+    /// KZG opening of blinded_commitment() at the evaluation point ω^index
+    pub fn blinded_opening(&self, index: usize) -> JZKZGOpening<C> {
+        open_fields(&self.crs, &self.blinded_fields(), index)
+    }
+}
+
+/// the bytes as a canonical scalar, see schema::to_field
+fn bytes_to_scalar<C: Bls12Config>(bytes: &[u8]) -> ScalarField<C> {
+    to_field::<ScalarField<C>>(bytes)
+}
+
+fn field_elements<C: Bls12Config>(fields: &[Vec<u8>]) -> Vec<ScalarField<C>> {
+    fields.iter().map(|field| bytes_to_scalar::<C>(field)).collect()
+}
+
+/// the bytes as little-endian u64 limbs, however many there are, so a
+/// point is multiplied by the whole integer as it is in-circuit
+fn le_limbs(bytes: &[u8]) -> Vec<u64> {
+    bytes
+        .chunks(8)
+        .map(|chunk| {
+            let mut limb = [0u8; 8];
+            limb[..chunk.len()].copy_from_slice(chunk);
+            u64::from_le_bytes(limb)
+        })
+        .collect()
+}
+
+/// Σ fields[i]·L_i, with each field read as a little-endian integer
+fn lagrange_commitment<C: Bls12Config>(
+    crs_lagrange: &[G1Projective<C>],
+    fields: &[Vec<u8>]
+) -> G1Projective<C> {
+    let mut acc = G1Projective::<C>::zero();
+    for (crs_elem, field) in crs_lagrange.iter().zip(fields.iter()) {
+        acc += crs_elem.mul_bigint(le_limbs(field));
+    }
+    acc
+}
+
+/// blind·L_0
+fn blind_commitment<C: Bls12Config>(
+    crs_lagrange: &[G1Projective<C>],
+    blind: &[u8]
+) -> G1Projective<C> {
+    crs_lagrange[0].mul_bigint(le_limbs(blind))
+}
+
+/// KZG CRS for a domain size only known at runtime, e.g. from a schema
+#[derive(Derivative)]
+#[derivative(Clone(bound = "C: Bls12Config"))]
+pub struct JZKZGDynCommitmentParams<const M: usize, C: Bls12Config>
+    where <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>
+{
+    /// KZG CRS in the coefficient basis
+    pub crs_coefficient_g1: Vec<G1Projective<C>>,
+    /// KZG CRS in the coefficient basis
+    pub crs_coefficient_g2: Vec<G2Projective<C>>,
+    /// KZG CRS in the Lagrange basis
+    pub crs_lagrange: Vec<G1Projective<C>>,
+}
+
+impl<const M: usize, C: Bls12Config> JZKZGDynCommitmentParams<M, C>
+    where <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>,
+{
+    /// the CRS for records of up to `size` fields, rounded up to a power of
+    /// two; fails if that exceeds the setup's max_size
+    pub fn from_universal(srs: &JZKZGUniversalParams<M, C>, size: usize) -> Result<Self, JZSchemaError> {
+        let n = size.next_power_of_two();
+        if n > srs.max_size() {
+            return Err(JZSchemaError::TooManyFields { capacity: srs.max_size(), actual: size });
         }
-        fields
+
+        Ok(JZKZGDynCommitmentParams {
+            crs_coefficient_g1: srs.powers_of_tau_g1[..4*n].to_vec(),
+            crs_coefficient_g2: srs.powers_of_tau_g2[..4*n].to_vec(),
+            crs_lagrange: srs.lagrange_crs(n),
+        })
     }
 
-    pub fn blinded_fields(&self) -> [ScalarField<C>; N] {
+    /// number of field slots
+    pub fn size(&self) -> usize {
+        self.crs_lagrange.len()
+    }
+}
+
+/// JZRecord with its number of fields chosen at runtime; slots of the
+/// CRS beyond the last field are committed as zero
+#[derive(Derivative)]
+#[derivative(Clone(bound = "C: Bls12Config"))]
+pub struct JZDynRecord<const M: usize, C: Bls12Config>
+    where <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>
+{
+    pub crs: JZKZGDynCommitmentParams<M, C>,
+    pub fields: Vec<Vec<u8>>, //first field is the entropy
+    pub blind: Vec<u8>, //in case we want to reveal a blinded commitment
+}
+
+impl<const M: usize, C: Bls12Config> JZDynRecord<M, C>
+    where <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>
+{
+    /// fails if there are no fields, more than the CRS has slots, or a
+    /// field or the blind is not a canonical scalar
+    pub fn new(
+        crs: &JZKZGDynCommitmentParams<M, C>,
+        fields: &[Vec<u8>],
+        blind: &Vec<u8>
+    ) -> Result<Self, JZSchemaError> {
+        if fields.is_empty() {
+            return Err(JZSchemaError::NoFields);
+        }
+        if fields.len() > crs.size() {
+            return Err(JZSchemaError::TooManyFields { capacity: crs.size(), actual: fields.len() });
+        }
+        if !fields.iter().all(|field| is_canonical::<ScalarField<C>>(field)) {
+            return Err(JZSchemaError::NonCanonical { field: "field" });
+        }
+        if !is_canonical::<ScalarField<C>>(blind) {
+            return Err(JZSchemaError::NonCanonical { field: "blind" });
+        }
+
+        Ok(JZDynRecord {
+            crs: crs.clone(),
+            fields: fields.to_vec(),
+            blind: blind.to_owned(),
+        })
+    }
+
+    /// builds a record with the schema's arity, checking the encoding is canonical
+    pub fn from_schema<const N: usize, S: JZRecordSchema<N>>(
+        crs: &JZKZGDynCommitmentParams<M, C>,
+        value: &S,
+        blind: &Vec<u8>
+    ) -> Result<Self, JZSchemaError> {
+        let fields = value.encode::<ScalarField<C>>()?;
+        Self::new(crs, &fields, blind)
+    }
+
+    pub fn to_schema<const N: usize, S: JZRecordSchema<N>>(&self) -> Result<S, JZSchemaError> {
+        S::decode::<ScalarField<C>>(&fields_array::<N>(&self.fields)?)
+    }
+
+    pub fn num_fields(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn commitment(&self) -> G1Projective<C> {
+        lagrange_commitment::<C>(&self.crs.crs_lagrange, &self.fields)
+    }
+
+    pub fn blinded_commitment(&self) -> G1Projective<C> {
+        self.commitment() + blind_commitment::<C>(&self.crs.crs_lagrange, &self.blind)
+    }
+
+    pub fn fields(&self) -> Vec<ScalarField<C>> {
+        field_elements::<C>(&self.fields)
+    }
+
+    pub fn blinded_fields(&self) -> Vec<ScalarField<C>> {
         let mut fields = self.fields();
+        fields[0] += bytes_to_scalar::<C>(&self.blind);
+        fields
+    }
+}
+
+/// KZG evaluation proof: the committed polynomial evaluates to `value` at ω^index
+#[derive(Derivative)]
+#[derivative(Clone(bound = "C: Bls12Config"))]
+pub struct JZKZGOpening<C: Bls12Config> {
+    pub index: usize,
+    pub value: ScalarField<C>,
+    /// commitment to the quotient (f(X) - value) / (X - ω^index)
+    pub proof: G1Projective<C>,
+}
 
-        // convert blind to a field element
-        let blind = ScalarField::<C>::from(
-            BigInt::<M>::from_bits_le(
-                utils::bytes_to_bits(&self.blind).as_slice()
-            )
+fn open_fields<const N: usize, const M: usize, C: Bls12Config>(
+    crs: &JZKZGCommitmentParams<N, M, C>,
+    fields: &[ScalarField<C>; N],
+    index: usize
+) -> JZKZGOpening<C>
+    where <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>
+{
+    assert!(index < N, "opening index {} out of bounds", index);
+
+    // the record polynomial f(X) has the fields as evaluations over the domain
+    let domain = Radix2EvaluationDomain::<ScalarField<C>>::new(N).unwrap();
+    let f = Evaluations::from_vec_and_domain(fields.to_vec(), domain).interpolate();
+
+    let point = domain.element(index);
+    let value = fields[index];
+
+    // q(X) = (f(X) - f(ω^i)) / (X - ω^i)
+    let numerator = &f - &DensePolynomial::from_coefficients_vec(vec![value]);
+    let divisor = DensePolynomial::from_coefficients_vec(vec![-point, ScalarField::<C>::one()]);
+    let quotient = &numerator / &divisor;
+
+    let mut proof = G1Projective::<C>::zero();
+    for (i, coeff) in quotient.coeffs.iter().enumerate() {
+        proof += crs.crs_coefficient_g1[i].mul_bigint(coeff.into_bigint());
+    }
+
+    JZKZGOpening { index, value, proof }
+}
+
+/// checks e(C - v·G + ω^i·π, H) = e(π, τ·H), which is the usual KZG check
+/// e(C - v·G, H) = e(π, τ·H - ω^i·H) with the index moved over to G1
+pub fn verify_opening<const N: usize, const M: usize, C: Bls12Config>(
+    crs: &JZKZGCommitmentParams<N, M, C>,
+    commitment: &G1Projective<C>,
+    opening: &JZKZGOpening<C>
+) -> bool
+    where <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>
+{
+    if opening.index >= N {
+        return false;
+    }
+
+    let domain = Radix2EvaluationDomain::<ScalarField<C>>::new(N).unwrap();
+    let point = domain.element(opening.index);
+
+    let g = crs.crs_coefficient_g1[0];
+    let h = crs.crs_coefficient_g2[0];
+    let tau_h = crs.crs_coefficient_g2[1];
+
+    let lhs = *commitment
+        - g.mul_bigint(opening.value.into_bigint())
+        + opening.proof.mul_bigint(point.into_bigint());
+
+    Bls12::<C>::multi_pairing(
+        [lhs.into_affine(), (-opening.proof).into_affine()],
+        [h.into_affine(), tau_h.into_affine()]
+    ).is_zero()
+}
+
+/// verifies openings of several commitments with one pairing check, on
+/// the γ-linear combination of the individual checks, where γ is drawn
+/// from a transcript of every commitment and opening
+pub fn batch_verify_openings<const N: usize, const M: usize, C: Bls12Config>(
+    crs: &JZKZGCommitmentParams<N, M, C>,
+    commitments: &[G1Projective<C>],
+    openings: &[JZKZGOpening<C>]
+) -> bool
+    where <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>
+{
+    if commitments.len() != openings.len() || openings.iter().any(|opening| opening.index >= N) {
+        return false;
+    }
+
+    let mut transcript = JZTranscript::new(b"jzexe.kzg.batch");
+    for (commitment, opening) in commitments.iter().zip(openings.iter()) {
+        transcript.append_point(b"commitment", commitment);
+        transcript.append_u64(b"index", opening.index as u64);
+        transcript.append_scalar(b"value", &opening.value);
+        transcript.append_point(b"proof", &opening.proof);
+    }
+    let gamma: ScalarField<C> = transcript.challenge_scalar(b"gamma");
+
+    let domain = Radix2EvaluationDomain::<ScalarField<C>>::new(N).unwrap();
+    let g = crs.crs_coefficient_g1[0];
+    let h = crs.crs_coefficient_g2[0];
+    let tau_h = crs.crs_coefficient_g2[1];
+
+    let mut lhs = G1Projective::<C>::zero();
+    let mut proofs = G1Projective::<C>::zero();
+    let mut power = ScalarField::<C>::one();
+    for (commitment, opening) in commitments.iter().zip(openings.iter()) {
+        let point = domain.element(opening.index);
+        let term = *commitment
+            - g.mul_bigint(opening.value.into_bigint())
+            + opening.proof.mul_bigint(point.into_bigint());
+
+        lhs += term.mul_bigint(power.into_bigint());
+        proofs += opening.proof.mul_bigint(power.into_bigint());
+        power *= gamma;
+    }
+
+    Bls12::<C>::multi_pairing(
+        [lhs.into_affine(), (-proofs).into_affine()],
+        [h.into_affine(), tau_h.into_affine()]
+    ).is_zero()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_poly::Polynomial;
+    use ark_std::test_rng;
+    use rand::SeedableRng;
+    use crate::utils;
+
+    type C = ark_bls12_377::Config;
+
+    #[test]
+    fn test_kzg_opening() {
+        let mut rng = test_rng();
+        let crs = JZKZGCommitmentParams::<8, 4, C>::trusted_setup(&mut rng);
+
+        let fields: [Vec<u8>; 8] = [
+            vec![1u8; 24], vec![2u8; 31], vec![40u8], vec![10u8],
+            vec![0u8], vec![0u8], vec![0u8], vec![7u8; 32],
+        ];
+        let record = JZRecord::<8, 4, C>::new(&crs, &fields, &vec![9u8; 24]);
+
+        for i in 0..8 {
+            let opening = record.opening(i);
+            assert_eq!(opening.value, record.fields()[i]);
+            assert!(verify_opening(&crs, &record.commitment(), &opening));
+
+            let blinded_opening = record.blinded_opening(i);
+            assert!(verify_opening(&crs, &record.blinded_commitment(), &blinded_opening));
+        }
+
+        // a wrong value must not verify
+        let mut opening = record.opening(3);
+        opening.value += ScalarField::<C>::one();
+        assert!(!verify_opening(&crs, &record.commitment(), &opening));
+
+        // nor may an opening be replayed at another index
+        let mut opening = record.opening(3);
+        opening.index = 4;
+        assert!(!verify_opening(&crs, &record.commitment(), &opening));
+        // fields are read whole: zero padding past 8·M bytes is not truncated
+        // into a different value, and commits to the same integer
+        let mut padded = fields.clone();
+        padded[3].resize(40, 0);
+        let padded = JZRecord::<8, 4, C>::new(&crs, &padded, &vec![9u8; 24]);
+        assert_eq!(padded.fields(), record.fields());
+        assert_eq!(padded.commitment(), record.commitment());
+    }
+
+    #[test]
+    fn test_kzg_batch_opening() {
+        let mut rng = test_rng();
+        let crs = JZKZGCommitmentParams::<4, 4, C>::trusted_setup(&mut rng);
+
+        let record_a = JZRecord::<4, 4, C>::new(
+            &crs, &[vec![1u8; 24], vec![2u8; 31], vec![40u8], vec![10u8]], &vec![9u8; 24]
+        );
+        let record_b = JZRecord::<4, 4, C>::new(
+            &crs, &[vec![3u8; 24], vec![4u8; 31], vec![41u8], vec![11u8]], &vec![8u8; 24]
         );
 
-        fields[0] += blind;
-        fields
+        let commitments = [record_a.commitment(), record_a.commitment(), record_b.blinded_commitment()];
+        let mut openings = [record_a.opening(1), record_a.opening(3), record_b.blinded_opening(2)];
+        assert!(batch_verify_openings(&crs, &commitments, &openings));
+
+        // one wrong value spoils the batch
+        openings[2].value += ScalarField::<C>::one();
+        assert!(!batch_verify_openings(&crs, &commitments, &openings));
+
+        // and so do mismatched lengths
+        assert!(!batch_verify_openings(&crs, &commitments[..2], &openings));
+    }
+
+    #[test]
+    fn test_universal_setup() {
+        let seed = [0u8; 32];
+        let srs = JZKZGUniversalParams::<4, C>::trusted_setup(8, &mut rand_chacha::ChaCha8Rng::from_seed(seed));
+        // replays the setup's rng to recover tau
+        let tau = ScalarField::<C>::rand(&mut rand_chacha::ChaCha8Rng::from_seed(seed));
+        let g = G1Projective::<C>::generator();
+
+        // the group inverse FFT matches evaluating each Lagrange polynomial at tau
+        for n in [1, 2, 4, 8] {
+            let expected: Vec<G1Projective<C>> = (0..n)
+                .map(|i| g.mul_bigint(utils::lagrange_poly(n, i).evaluate(&tau).into_bigint()))
+                .collect();
+            assert_eq!(srs.lagrange_crs(n), expected);
+        }
+
+        // records of different arities share the setup
+        let crs_4 = srs.specialize::<4>();
+        let crs_8 = srs.specialize::<8>();
+        assert_eq!(crs_4.crs_coefficient_g1[..], crs_8.crs_coefficient_g1[..16]);
+
+        let record = JZRecord::<4, 4, C>::new(
+            &crs_4, &[vec![1u8; 24], vec![2u8; 31], vec![40u8], vec![10u8]], &vec![9u8; 24]
+        );
+        let opening = record.opening(2);
+        assert!(verify_opening(&crs_4, &record.commitment(), &opening));
     }
 }
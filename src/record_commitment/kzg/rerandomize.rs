@@ -0,0 +1,132 @@
+use ark_ec::*;
+use ark_ec::models::bls12::*;
+use ark_ff::*;
+use ark_std::{*, rand::Rng};
+
+use crate::transcript::JZTranscript;
+use super::{JZRecord, JZKZGCommitmentParams, ScalarField};
+
+/// Schnorr proof of knowledge of δ such that new - old = δ·L_0, i.e. both
+/// blinded commitments open to the same fields and differ only in the blind.
+/// The verifier needs both commitments, so the proof links them: it is only
+/// for parties who already know the two. To keep new unlinkable to old,
+/// prove the relation in-circuit with generate_rerandomization_constraints,
+/// with old hidden (e.g. as a tree leaf) and only new public.
+#[derive(Derivative)]
+#[derivative(Clone(bound = "C: Bls12Config"))]
+pub struct JZRerandomizationProof<C: Bls12Config> {
+    /// R = k·L_0
+    pub nonce_commitment: G1Projective<C>,
+    /// s = k + c·δ
+    pub response: ScalarField<C>,
+}
+
+fn challenge<const N: usize, const M: usize, C: Bls12Config>(
+    crs: &JZKZGCommitmentParams<N, M, C>,
+    old: &G1Projective<C>,
+    new: &G1Projective<C>,
+    nonce_commitment: &G1Projective<C>
+) -> ScalarField<C>
+    where <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>
+{
+    let mut transcript = JZTranscript::new(b"jzexe.kzg.rerandomize");
+    transcript.append_message(b"crs", &crs.digest());
+    transcript.append_point(b"old", old);
+    transcript.append_point(b"new", new);
+    transcript.append_point(b"nonce_commitment", nonce_commitment);
+
+    transcript.challenge_scalar(b"challenge")
+}
+
+/// returns old + δ·L_0 for a fresh δ, along with δ and a proof that the
+/// two commitments hide the same fields; anyone holding a blinded
+/// commitment can do this, without knowing its opening
+pub fn rerandomize_commitment<const N: usize, const M: usize, C: Bls12Config, R: Rng>(
+    crs: &JZKZGCommitmentParams<N, M, C>,
+    old: &G1Projective<C>,
+    rng: &mut R
+) -> (G1Projective<C>, ScalarField<C>, JZRerandomizationProof<C>)
+    where <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>
+{
+    let delta = ScalarField::<C>::rand(rng);
+    let new = *old + crs.crs_lagrange[0].mul_bigint(delta.into_bigint());
+
+    let nonce = ScalarField::<C>::rand(rng);
+    let nonce_commitment = crs.crs_lagrange[0].mul_bigint(nonce.into_bigint());
+
+    let c = challenge(crs, old, &new, &nonce_commitment);
+    let response = nonce + c * delta;
+
+    (new, delta, JZRerandomizationProof { nonce_commitment, response })
+}
+
+/// checks s·L_0 = R + c·(new - old)
+pub fn verify_rerandomization<const N: usize, const M: usize, C: Bls12Config>(
+    crs: &JZKZGCommitmentParams<N, M, C>,
+    old: &G1Projective<C>,
+    new: &G1Projective<C>,
+    proof: &JZRerandomizationProof<C>
+) -> bool
+    where <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>
+{
+    let c = challenge(crs, old, new, &proof.nonce_commitment);
+
+    crs.crs_lagrange[0].mul_bigint(proof.response.into_bigint())
+        == proof.nonce_commitment + (*new - old).mul_bigint(c.into_bigint())
+}
+
+impl<const N: usize, const M: usize, C: Bls12Config> JZRecord<N, M, C>
+    where <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>
+{
+    /// the same record under a fresh blind, whose blinded commitment is
+    /// unlinkable to ours as long as the returned proof, which relates the
+    /// two blinded commitments, is not published (see JZRerandomizationProof)
+    pub fn rerandomize<R: Rng>(&self, rng: &mut R) -> (Self, JZRerandomizationProof<C>) {
+        let (_, delta, proof) = rerandomize_commitment(&self.crs, &self.blinded_commitment(), rng);
+
+        let blind = super::bytes_to_scalar::<C>(&self.blind) + delta;
+        let record = JZRecord::new(&self.crs, &self.fields, &blind.into_bigint().to_bytes_le());
+
+        (record, proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::test_rng;
+
+    type C = ark_bls12_377::Config;
+
+    #[test]
+    fn test_rerandomize() {
+        let mut rng = test_rng();
+        let crs = JZKZGCommitmentParams::<4, 4, C>::trusted_setup(&mut rng);
+
+        let fields: [Vec<u8>; 4] = [vec![1u8; 24], vec![2u8; 31], vec![40u8], vec![10u8]];
+        let record = JZRecord::<4, 4, C>::new(&crs, &fields, &vec![9u8; 24]);
+
+        let (rerandomized, proof) = record.rerandomize(&mut rng);
+        assert_eq!(rerandomized.commitment(), record.commitment());
+        assert_ne!(rerandomized.blinded_commitment(), record.blinded_commitment());
+        assert!(verify_rerandomization(
+            &crs, &record.blinded_commitment(), &rerandomized.blinded_commitment(), &proof
+        ));
+
+        // a third party can re-randomize again without the opening
+        let (again, _, proof) = rerandomize_commitment(&crs, &rerandomized.blinded_commitment(), &mut rng);
+        assert!(verify_rerandomization(&crs, &rerandomized.blinded_commitment(), &again, &proof));
+
+        // but cannot claim a commitment to other fields is a re-randomization
+        let other = JZRecord::<4, 4, C>::new(
+            &crs, &[vec![1u8; 24], vec![2u8; 31], vec![40u8], vec![11u8]], &vec![9u8; 24]
+        );
+        assert!(!verify_rerandomization(
+            &crs, &record.blinded_commitment(), &other.blinded_commitment(), &proof
+        ));
+
+        // nor reuse a proof under another setup
+        let other_crs = JZKZGCommitmentParams::<4, 4, C>::trusted_setup(&mut rng);
+        assert!(!verify_rerandomization(&other_crs, &rerandomized.blinded_commitment(), &again, &proof));
+    }
+}
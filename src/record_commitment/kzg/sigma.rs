@@ -0,0 +1,235 @@
+use ark_ec::*;
+use ark_ec::models::bls12::*;
+use ark_ff::*;
+use ark_std::{*, rand::Rng};
+
+use crate::transcript::JZTranscript;
+use super::{JZRecord, JZKZGCommitmentParams, ScalarField};
+
+/// Okamoto proof of knowledge of an opening of a blinded commitment
+/// C = Σ f_i·L_i + b·L_0. The blind shares L_0 with the entropy field,
+/// so the proof is over the blinded fields (f_0 + b, f_1, ..., f_{N-1}).
+///
+/// The extracted fields are the committed ones only while nobody knows
+/// tau: with it, one commitment has openings to any fields (see
+/// JZKZGUniversalParams::trusted_setup). Challenges bind the CRS digest,
+/// so proofs do not carry over to other parameters.
+#[derive(Derivative)]
+#[derivative(Clone(bound = "C: Bls12Config"))]
+pub struct JZOpeningKnowledgeProof<C: Bls12Config> {
+    /// R = Σ k_i·L_i
+    pub nonce_commitment: G1Projective<C>,
+    /// s_i = k_i + c·f_i
+    pub responses: Vec<ScalarField<C>>,
+}
+
+/// two Okamoto proofs sharing the nonce, and hence the response, at one
+/// field index; this shows both commitments hold the same value there, under
+/// the same assumption on tau as JZOpeningKnowledgeProof
+#[derive(Derivative)]
+#[derivative(Clone(bound = "C: Bls12Config"))]
+pub struct JZFieldEqualityProof<C: Bls12Config> {
+    pub proof_a: JZOpeningKnowledgeProof<C>,
+    pub proof_b: JZOpeningKnowledgeProof<C>,
+}
+
+fn challenge<const N: usize, const M: usize, C: Bls12Config>(
+    crs: &JZKZGCommitmentParams<N, M, C>,
+    domain: &[u8],
+    points: &[&G1Projective<C>],
+    index: usize
+) -> ScalarField<C>
+    where <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>
+{
+    let mut transcript = JZTranscript::new(domain);
+    transcript.append_message(b"crs", &crs.digest());
+    transcript.append_u64(b"index", index as u64);
+    for point in points {
+        transcript.append_point(b"point", *point);
+    }
+
+    transcript.challenge_scalar(b"challenge")
+}
+
+fn msm<const N: usize, const M: usize, C: Bls12Config>(
+    crs: &JZKZGCommitmentParams<N, M, C>,
+    scalars: &[ScalarField<C>]
+) -> G1Projective<C>
+    where <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>
+{
+    let mut acc = G1Projective::<C>::zero();
+    for (crs_elem, scalar) in crs.crs_lagrange.iter().zip(scalars.iter()) {
+        acc += crs_elem.mul_bigint(scalar.into_bigint());
+    }
+    acc
+}
+
+fn respond<C: Bls12Config>(
+    nonces: &[ScalarField<C>],
+    witnesses: &[ScalarField<C>],
+    c: ScalarField<C>
+) -> Vec<ScalarField<C>> {
+    nonces.iter().zip(witnesses.iter()).map(|(k, w)| *k + c * w).collect()
+}
+
+fn check<const N: usize, const M: usize, C: Bls12Config>(
+    crs: &JZKZGCommitmentParams<N, M, C>,
+    commitment: &G1Projective<C>,
+    proof: &JZOpeningKnowledgeProof<C>,
+    c: ScalarField<C>
+) -> bool
+    where <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>
+{
+    proof.responses.len() == N
+        && msm(crs, &proof.responses) == proof.nonce_commitment + commitment.mul_bigint(c.into_bigint())
+}
+
+const KNOWLEDGE_DOMAIN: &[u8] = b"jzexe.kzg.sigma.knowledge";
+const EQUALITY_DOMAIN: &[u8] = b"jzexe.kzg.sigma.equality";
+
+pub fn prove_knowledge<const N: usize, const M: usize, C: Bls12Config, R: Rng>(
+    record: &JZRecord<N, M, C>,
+    rng: &mut R
+) -> JZOpeningKnowledgeProof<C>
+    where <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>
+{
+    let nonces: Vec<ScalarField<C>> = (0..N).map(|_| ScalarField::<C>::rand(rng)).collect();
+    let nonce_commitment = msm(&record.crs, &nonces);
+
+    let c = challenge(&record.crs, KNOWLEDGE_DOMAIN, &[&record.blinded_commitment(), &nonce_commitment], 0);
+    let responses = respond::<C>(&nonces, &record.blinded_fields(), c);
+
+    JZOpeningKnowledgeProof { nonce_commitment, responses }
+}
+
+/// checks Σ s_i·L_i = R + c·C
+pub fn verify_knowledge<const N: usize, const M: usize, C: Bls12Config>(
+    crs: &JZKZGCommitmentParams<N, M, C>,
+    commitment: &G1Projective<C>,
+    proof: &JZOpeningKnowledgeProof<C>
+) -> bool
+    where <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>
+{
+    let c = challenge(crs, KNOWLEDGE_DOMAIN, &[commitment, &proof.nonce_commitment], 0);
+    check(crs, commitment, proof, c)
+}
+
+/// proves knowledge of openings of both blinded commitments that agree on
+/// field `index`; the entropy slot 0 carries the blind and cannot be compared
+pub fn prove_field_equality<const N: usize, const M: usize, C: Bls12Config, R: Rng>(
+    record_a: &JZRecord<N, M, C>,
+    record_b: &JZRecord<N, M, C>,
+    index: usize,
+    rng: &mut R
+) -> JZFieldEqualityProof<C>
+    where <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>
+{
+    assert!(index > 0 && index < N, "field index {} cannot be compared", index);
+    assert!(
+        record_a.fields()[index] == record_b.fields()[index],
+        "records differ at field {}", index
+    );
+
+    let nonces_a: Vec<ScalarField<C>> = (0..N).map(|_| ScalarField::<C>::rand(rng)).collect();
+    let mut nonces_b: Vec<ScalarField<C>> = (0..N).map(|_| ScalarField::<C>::rand(rng)).collect();
+    nonces_b[index] = nonces_a[index];
+
+    let nonce_commitment_a = msm(&record_a.crs, &nonces_a);
+    let nonce_commitment_b = msm(&record_b.crs, &nonces_b);
+
+    let c = challenge(
+        &record_a.crs,
+        EQUALITY_DOMAIN,
+        &[
+            &record_a.blinded_commitment(),
+            &record_b.blinded_commitment(),
+            &nonce_commitment_a,
+            &nonce_commitment_b
+        ],
+        index
+    );
+
+    JZFieldEqualityProof {
+        proof_a: JZOpeningKnowledgeProof {
+            nonce_commitment: nonce_commitment_a,
+            responses: respond::<C>(&nonces_a, &record_a.blinded_fields(), c),
+        },
+        proof_b: JZOpeningKnowledgeProof {
+            nonce_commitment: nonce_commitment_b,
+            responses: respond::<C>(&nonces_b, &record_b.blinded_fields(), c),
+        },
+    }
+}
+
+pub fn verify_field_equality<const N: usize, const M: usize, C: Bls12Config>(
+    crs: &JZKZGCommitmentParams<N, M, C>,
+    commitment_a: &G1Projective<C>,
+    commitment_b: &G1Projective<C>,
+    index: usize,
+    proof: &JZFieldEqualityProof<C>
+) -> bool
+    where <<C as Bls12Config>::G1Config as CurveConfig>::ScalarField: std::convert::From<BigInt<M>>
+{
+    if index == 0 || index >= N {
+        return false;
+    }
+
+    let c = challenge(
+        crs,
+        EQUALITY_DOMAIN,
+        &[
+            commitment_a,
+            commitment_b,
+            &proof.proof_a.nonce_commitment,
+            &proof.proof_b.nonce_commitment
+        ],
+        index
+    );
+
+    check(crs, commitment_a, &proof.proof_a, c)
+        && check(crs, commitment_b, &proof.proof_b, c)
+        && proof.proof_a.responses[index] == proof.proof_b.responses[index]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::test_rng;
+
+    type C = ark_bls12_377::Config;
+    const OWNER: usize = 1;
+
+    #[test]
+    fn test_sigma_proofs() {
+        let mut rng = test_rng();
+        let crs = JZKZGCommitmentParams::<4, 4, C>::trusted_setup(&mut rng);
+
+        let record_a = JZRecord::<4, 4, C>::new(
+            &crs, &[vec![1u8; 24], vec![2u8; 31], vec![40u8], vec![10u8]], &vec![9u8; 24]
+        );
+        let record_b = JZRecord::<4, 4, C>::new(
+            &crs, &[vec![5u8; 24], vec![2u8; 31], vec![41u8], vec![3u8]], &vec![8u8; 24]
+        );
+        let com_a = record_a.blinded_commitment();
+        let com_b = record_b.blinded_commitment();
+
+        let proof = prove_knowledge(&record_a, &mut rng);
+        assert!(verify_knowledge(&crs, &com_a, &proof));
+        assert!(!verify_knowledge(&crs, &com_b, &proof));
+
+        // same owner in both records
+        let proof = prove_field_equality(&record_a, &record_b, OWNER, &mut rng);
+        assert!(verify_field_equality(&crs, &com_a, &com_b, OWNER, &proof));
+        assert!(!verify_field_equality(&crs, &com_a, &com_b, OWNER + 1, &proof));
+
+        // responses that disagree at the index are rejected
+        let mut forged = proof.clone();
+        forged.proof_b.responses[OWNER] += ScalarField::<C>::one();
+        assert!(!verify_field_equality(&crs, &com_a, &com_b, OWNER, &forged));
+
+        // proofs are bound to the setup they were made under
+        let other_crs = JZKZGCommitmentParams::<4, 4, C>::trusted_setup(&mut rng);
+        let proof = prove_knowledge(&record_a, &mut rng);
+        assert!(!verify_knowledge(&other_crs, &com_a, &proof));
+    }
+}
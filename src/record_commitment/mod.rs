@@ -1,2 +1,7 @@
 pub mod kzg;
 pub mod sha256;
+pub mod poseidon;
+pub mod pedersen_vector;
+pub mod cross_group;
+pub mod schema;
+pub mod packing;
@@ -0,0 +1,89 @@
+use ark_ff::*;
+use ark_relations::r1cs::*;
+use ark_r1cs_std::bits::uint8::UInt8;
+
+use super::JZPackingLayout;
+
+/// in-circuit counterpart of JZPackingLayout::unpack. Fields are
+/// byte-aligned within their slot, so unpacking only selects the committed
+/// slot bytes and costs no constraints; the slot lengths are fixed at
+/// allocation time, so a mismatch means the record used another layout
+pub fn unpack_field_vars<ConstraintF: PrimeField>(
+    layout: &JZPackingLayout,
+    slots: &[Vec<UInt8<ConstraintF>>]
+) -> Result<Vec<Vec<UInt8<ConstraintF>>>> {
+    if slots.len() != layout.num_slots() {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+    for (s, slot) in slots.iter().enumerate() {
+        if slot.len() != layout.slot_len(s) {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+    }
+
+    Ok(layout.positions()
+        .iter()
+        .zip(layout.fields.iter())
+        .map(|(&(s, offset), field)| slots[s][offset..offset + field.len].to_vec())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_r1cs_std::prelude::*;
+    use ark_std::test_rng;
+    use crate::record_commitment::kzg::{self, *, constraints::*};
+    use crate::record_commitment::schema::{*, constraints::*};
+
+    type C = ark_bls12_377::Config;
+    type ConstraintF = ark_bls12_377::Fq;
+
+    #[test]
+    fn test_packed_coin() {
+        let mut rng = test_rng();
+        let srs = JZKZGUniversalParams::<4, C>::trusted_setup(8, &mut rng);
+        let layout = JZPackingLayout::coin();
+
+        let coin = JZCoin {
+            entropy: [3u8; ENTROPY_LEN],
+            owner: [7u8; KEY_LEN],
+            asset_id: 40,
+            amount: 10,
+            app_id: 1,
+            app_data: [5, 6],
+            rho: [9u8; KEY_LEN],
+        };
+        let fields = coin.encode::<ark_bls12_377::Fr>().unwrap();
+        let blind = vec![1u8; ENTROPY_LEN];
+
+        let crs = JZKZGDynCommitmentParams::from_universal(&srs, layout.num_slots()).unwrap();
+        let packed = JZDynRecord::new(&crs, &layout.pack(&fields).unwrap(), &blind).unwrap();
+        let unpacked = JZRecord::<8, 4, C>::new(&srs.specialize::<8>(), &fields, &blind);
+
+        let cs = ConstraintSystem::<ark_bw6_761::Fr>::new_ref();
+
+        let crs_var = JZKZGDynCommitmentParamsVar::<C>::new_constant(cs.clone(), &crs).unwrap();
+        let packed_var = JZDynRecordVar::<C, ConstraintF>::new_witness(cs.clone(), || Ok(&packed)).unwrap();
+        let before = cs.num_constraints();
+        generate_dyn_constraints(cs.clone(), &crs_var, &packed_var, layout.num_slots()).unwrap();
+        let packed_constraints = cs.num_constraints() - before;
+
+        let field_vars = packed_var.unpacked_fields(&layout).unwrap();
+        let coin_var = JZCoinVar::from_field_vars(&field_vars.try_into().unwrap()).unwrap();
+        assert_eq!(coin_var.amount.value().unwrap(), 10);
+        assert_eq!(coin_var.app_data[1].value().unwrap(), 6);
+
+        let crs_var = JZKZGCommitmentParamsVar::<8, C>::new_constant(cs.clone(), srs.specialize::<8>()).unwrap();
+        let unpacked_var = JZRecordVar::<8, C, ConstraintF>::new_witness(cs.clone(), || Ok(&unpacked)).unwrap();
+        let before = cs.num_constraints();
+        kzg::constraints::generate_constraints(cs.clone(), &crs_var, &unpacked_var).unwrap();
+        let unpacked_constraints = cs.num_constraints() - before;
+
+        assert!(cs.is_satisfied().unwrap(), "constraints not satisfied");
+        assert!(
+            packed_constraints < unpacked_constraints,
+            "{} packed vs {} unpacked constraints", packed_constraints, unpacked_constraints
+        );
+    }
+}
@@ -0,0 +1,185 @@
+pub mod constraints;
+
+use ark_std::*;
+
+use crate::record_commitment::schema::*;
+
+/// bytes that always fit below the scalar fields we use, so a packed slot
+/// is canonical whatever the fields in it hold
+pub const SLOT_CAPACITY: usize = ENTROPY_LEN;
+
+/// a logical record field of fixed byte length
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JZPackedField {
+    pub name: &'static str,
+    pub len: usize,
+}
+
+/// Assigns the logical fields of a record to commitment slots. The fields
+/// of a slot are concatenated in order, i.e. field k sits at bit offset
+/// 8·(bytes before it) of the slot's little-endian integer, so a slot
+/// holding a u64 amount and a one byte flag costs one scalar multiplication
+/// instead of two. The entropy (field 0) stays alone in slot 0, which is
+/// where the blind goes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JZPackingLayout {
+    pub fields: Vec<JZPackedField>,
+    /// indices into `fields`, per slot
+    pub slots: Vec<Vec<usize>>,
+}
+
+impl JZPackingLayout {
+    /// checks that every field lands in exactly one slot, that the entropy
+    /// has slot 0 to itself, and that shared slots fit in SLOT_CAPACITY
+    pub fn new(
+        fields: Vec<JZPackedField>,
+        slots: Vec<Vec<usize>>
+    ) -> Result<Self, JZSchemaError> {
+        let mut seen = vec![false; fields.len()];
+        for slot in slots.iter() {
+            for &i in slot.iter() {
+                if i >= fields.len() || seen[i] {
+                    return Err(JZSchemaError::InvalidLayout { reason: "fields must be placed exactly once" });
+                }
+                seen[i] = true;
+            }
+
+            let len: usize = slot.iter().map(|&i| fields[i].len).sum();
+            if slot.is_empty() || (slot.len() > 1 && len > SLOT_CAPACITY) {
+                return Err(JZSchemaError::InvalidLayout { reason: "slot is empty or over capacity" });
+            }
+        }
+
+        if seen.iter().any(|&s| !s) {
+            return Err(JZSchemaError::InvalidLayout { reason: "fields must be placed exactly once" });
+        }
+        if slots.first() != Some(&vec![0]) {
+            return Err(JZSchemaError::InvalidLayout { reason: "the entropy must be alone in slot 0" });
+        }
+
+        Ok(JZPackingLayout { fields, slots })
+    }
+
+    /// entropy in slot 0, then every other field goes into the first slot
+    /// with room for it; fields longer than SLOT_CAPACITY get their own slot
+    pub fn greedy(fields: Vec<JZPackedField>) -> Self {
+        let mut slots: Vec<Vec<usize>> = vec![vec![0]];
+        let mut used: Vec<usize> = vec![SLOT_CAPACITY];
+
+        for (i, field) in fields.iter().enumerate().skip(1) {
+            match used.iter().position(|&u| u + field.len <= SLOT_CAPACITY) {
+                Some(slot) => {
+                    slots[slot].push(i);
+                    used[slot] += field.len;
+                },
+                None => {
+                    slots.push(vec![i]);
+                    used.push(cmp::min(field.len, SLOT_CAPACITY));
+                },
+            }
+        }
+
+        JZPackingLayout { fields, slots }
+    }
+
+    /// the greedy layout of a JZCoin: 8 fields in 5 slots
+    pub fn coin() -> Self {
+        let lengths = [ENTROPY_LEN, KEY_LEN, U64_LEN, U64_LEN, U64_LEN, U64_LEN, U64_LEN, KEY_LEN];
+        Self::greedy(
+            JZCoin::FIELD_NAMES.iter()
+                .zip(lengths.iter())
+                .map(|(&name, &len)| JZPackedField { name, len })
+                .collect()
+        )
+    }
+
+    pub fn num_slots(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// (slot, byte offset within the slot) of every field
+    pub fn positions(&self) -> Vec<(usize, usize)> {
+        let mut positions = vec![(0, 0); self.fields.len()];
+        for (s, slot) in self.slots.iter().enumerate() {
+            let mut offset = 0;
+            for &i in slot.iter() {
+                positions[i] = (s, offset);
+                offset += self.fields[i].len;
+            }
+        }
+        positions
+    }
+
+    /// total bytes of the fields in a slot
+    pub fn slot_len(&self, slot: usize) -> usize {
+        self.slots[slot].iter().map(|&i| self.fields[i].len).sum()
+    }
+
+    /// the slot bytes to commit to, e.g. as the fields of a JZDynRecord
+    pub fn pack(&self, fields: &[Vec<u8>]) -> Result<Vec<Vec<u8>>, JZSchemaError> {
+        if fields.len() != self.fields.len() {
+            return Err(JZSchemaError::ArityMismatch { expected: self.fields.len(), actual: fields.len() });
+        }
+
+        let mut slots = vec![];
+        for slot in self.slots.iter() {
+            let mut packed = vec![];
+            for &i in slot.iter() {
+                let field = &self.fields[i];
+                if fields[i].len() != field.len {
+                    return Err(JZSchemaError::InvalidLength {
+                        field: field.name, expected: field.len, actual: fields[i].len()
+                    });
+                }
+                packed.extend_from_slice(&fields[i]);
+            }
+            slots.push(packed);
+        }
+        Ok(slots)
+    }
+
+    /// inverse of pack
+    pub fn unpack(&self, slots: &[Vec<u8>]) -> Result<Vec<Vec<u8>>, JZSchemaError> {
+        if slots.len() != self.num_slots() {
+            return Err(JZSchemaError::ArityMismatch { expected: self.num_slots(), actual: slots.len() });
+        }
+        for (s, slot) in slots.iter().enumerate() {
+            if slot.len() != self.slot_len(s) {
+                return Err(JZSchemaError::InvalidLength {
+                    field: self.fields[self.slots[s][0]].name, expected: self.slot_len(s), actual: slot.len()
+                });
+            }
+        }
+
+        Ok(self.positions()
+            .iter()
+            .zip(self.fields.iter())
+            .map(|(&(s, offset), field)| slots[s][offset..offset + field.len].to_vec())
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coin_layout() {
+        let layout = JZPackingLayout::coin();
+        assert_eq!(layout.slots, vec![vec![0], vec![1], vec![2, 3, 4], vec![5, 6], vec![7]]);
+        assert_eq!(JZPackingLayout::new(layout.fields.clone(), layout.slots.clone()), Ok(layout.clone()));
+
+        let fields: Vec<Vec<u8>> = vec![
+            vec![3u8; ENTROPY_LEN], vec![7u8; KEY_LEN], encode_u64(40), encode_u64(10),
+            encode_u64(0), encode_u64(1), encode_u64(2), vec![9u8; KEY_LEN],
+        ];
+        let slots = layout.pack(&fields).unwrap();
+        assert_eq!(slots[2], [encode_u64(40), encode_u64(10), encode_u64(0)].concat());
+        assert_eq!(layout.unpack(&slots).unwrap(), fields);
+
+        // the entropy must have slot 0 to itself
+        assert!(JZPackingLayout::new(layout.fields.clone(), vec![vec![0, 2], vec![1], vec![3, 4], vec![5, 6], vec![7]]).is_err());
+        // and shared slots must not overflow
+        assert!(JZPackingLayout::new(layout.fields.clone(), vec![vec![0], vec![1, 2], vec![3, 4], vec![5, 6], vec![7]]).is_err());
+    }
+}
@@ -0,0 +1,174 @@
+use ark_ec::{*, twisted_edwards::{Projective, TECurveConfig}};
+use ark_ff::*;
+use ark_std::{*, rand::Rng};
+
+use crate::transcript::JZTranscript;
+use super::{JZRecord, JZPedersenVectorParams};
+
+/// Proof that the blinded commitments of some input and output records
+/// carry the same total in the amount field.
+///
+/// The difference D = Σ inputs - Σ outputs is Σ δ_i·G_i + δ_blind·H, and
+/// its coefficient on G_amount is the net amount. The proof is a multi-base
+/// Schnorr proof of knowledge of D's coefficients on every other G_i and on
+/// H. The generators are hashed to the curve, so no one knows a relation
+/// expressing G_amount in the others and the proof needs no trusted setup.
+/// Amounts add up modulo the group order, so callers must still keep them
+/// range-checked (e.g. inside the circuit) to rule out wrap-around.
+#[derive(Derivative)]
+#[derivative(Clone(bound = "P: TECurveConfig"))]
+pub struct JZBalanceProof<P: TECurveConfig> {
+    /// R = Σ_{i != amount} k_i·G_i + k·H
+    pub nonce_commitment: Projective<P>,
+    /// s_i = k_i + c·δ_i for every i != amount, in index order
+    pub responses: Vec<P::ScalarField>,
+    /// s = k + c·δ_blind
+    pub blind_response: P::ScalarField,
+}
+
+fn difference<P: TECurveConfig>(
+    inputs: &[Projective<P>],
+    outputs: &[Projective<P>]
+) -> Projective<P> {
+    inputs.iter().sum::<Projective<P>>() - outputs.iter().sum::<Projective<P>>()
+}
+
+fn challenge<const N: usize, P: TECurveConfig>(
+    params: &JZPedersenVectorParams<N, P>,
+    difference: &Projective<P>,
+    nonce_commitment: &Projective<P>,
+    amount_index: usize
+) -> P::ScalarField {
+    let mut transcript = JZTranscript::new(b"jzexe.pedersen_vector.balance");
+    for generator in params.generators.iter() {
+        transcript.append_point(b"generator", generator);
+    }
+    transcript.append_point(b"blinding_generator", &params.blinding_generator);
+    transcript.append_u64(b"amount_index", amount_index as u64);
+    transcript.append_point(b"difference", difference);
+    transcript.append_point(b"nonce_commitment", nonce_commitment);
+
+    transcript.challenge_scalar(b"challenge")
+}
+
+/// proves that Σ inputs and Σ outputs agree on the amount field of their
+/// blinded commitments; panics if they do not
+pub fn prove_balance<const N: usize, P: TECurveConfig, R: Rng>(
+    inputs: &[JZRecord<N, P>],
+    outputs: &[JZRecord<N, P>],
+    amount_index: usize,
+    rng: &mut R
+) -> JZBalanceProof<P> {
+    assert!(amount_index < N, "amount index {} out of bounds", amount_index);
+    let params = &inputs.first().or(outputs.first()).expect("no records").params;
+
+    // δ_i = Σ inputs_i - Σ outputs_i, and likewise for the blinds
+    let mut deltas = [P::ScalarField::zero(); N];
+    let mut blind_delta = P::ScalarField::zero();
+    for record in inputs.iter() {
+        for (delta, field) in deltas.iter_mut().zip(record.fields().iter()) {
+            *delta += field;
+        }
+        blind_delta += record.blind();
+    }
+    for record in outputs.iter() {
+        for (delta, field) in deltas.iter_mut().zip(record.fields().iter()) {
+            *delta -= field;
+        }
+        blind_delta -= record.blind();
+    }
+    assert!(deltas[amount_index].is_zero(), "input and output amounts do not balance");
+
+    let nonces: Vec<P::ScalarField> = (0..N)
+        .map(|_| P::ScalarField::rand(rng))
+        .collect();
+    let blind_nonce = P::ScalarField::rand(rng);
+
+    let mut nonce_commitment = params.blinding_generator.mul_bigint(blind_nonce.into_bigint());
+    for i in (0..N).filter(|&i| i != amount_index) {
+        nonce_commitment += params.generators[i].mul_bigint(nonces[i].into_bigint());
+    }
+
+    let input_coms: Vec<Projective<P>> = inputs.iter().map(|r| r.blinded_commitment()).collect();
+    let output_coms: Vec<Projective<P>> = outputs.iter().map(|r| r.blinded_commitment()).collect();
+    let c = challenge(params, &difference::<P>(&input_coms, &output_coms), &nonce_commitment, amount_index);
+
+    let responses = (0..N)
+        .filter(|&i| i != amount_index)
+        .map(|i| nonces[i] + c * deltas[i])
+        .collect();
+
+    JZBalanceProof { nonce_commitment, responses, blind_response: blind_nonce + c * blind_delta }
+}
+
+/// checks Σ_{i != amount} s_i·G_i + s·H = R + c·(Σ inputs - Σ outputs)
+pub fn verify_balance<const N: usize, P: TECurveConfig>(
+    params: &JZPedersenVectorParams<N, P>,
+    input_commitments: &[Projective<P>],
+    output_commitments: &[Projective<P>],
+    amount_index: usize,
+    proof: &JZBalanceProof<P>
+) -> bool {
+    if amount_index >= N || proof.responses.len() != N - 1 {
+        return false;
+    }
+
+    // a small-order component in D would not be caught by the equation
+    // below for every challenge
+    let in_subgroup = |point: &Projective<P>| point.into_affine().is_in_correct_subgroup_assuming_on_curve();
+    if !input_commitments.iter().chain(output_commitments.iter()).all(in_subgroup)
+        || !in_subgroup(&proof.nonce_commitment)
+    {
+        return false;
+    }
+
+    let diff = difference::<P>(input_commitments, output_commitments);
+    let c = challenge(params, &diff, &proof.nonce_commitment, amount_index);
+
+    let mut lhs = params.blinding_generator.mul_bigint(proof.blind_response.into_bigint());
+    let generators = (0..N).filter(|&i| i != amount_index).map(|i| params.generators[i]);
+    for (generator, response) in generators.zip(proof.responses.iter()) {
+        lhs += generator.mul_bigint(response.into_bigint());
+    }
+
+    lhs == proof.nonce_commitment + diff.mul_bigint(c.into_bigint())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::DEFAULT_DOMAIN;
+    use ark_std::test_rng;
+
+    type P = ark_ed_on_bls12_377::EdwardsConfig;
+    const AMOUNT: usize = 3;
+
+    fn coin(params: &JZPedersenVectorParams<4, P>, owner: u8, amount: u8, blind: u8) -> JZRecord<4, P> {
+        JZRecord::<4, P>::new(params, &[vec![owner; 31], vec![2u8], vec![40u8], vec![amount]], &vec![blind; 31])
+    }
+
+    #[test]
+    fn test_balance_proof() {
+        let mut rng = test_rng();
+        let params = JZPedersenVectorParams::<4, P>::from_domain(DEFAULT_DOMAIN);
+
+        // owners and blinds differ freely, only the amounts must balance
+        let inputs = [coin(&params, 1, 30, 5), coin(&params, 1, 12, 6)];
+        let outputs = [coin(&params, 2, 40, 7), coin(&params, 1, 2, 9)];
+
+        let input_coms: Vec<_> = inputs.iter().map(|r| r.blinded_commitment()).collect();
+        let output_coms: Vec<_> = outputs.iter().map(|r| r.blinded_commitment()).collect();
+
+        let proof = prove_balance(&inputs, &outputs, AMOUNT, &mut rng);
+        assert!(verify_balance(&params, &input_coms, &output_coms, AMOUNT, &proof));
+
+        // the proof does not transfer to commitments with a different net amount
+        let inflated = [coin(&params, 2, 41, 7), coin(&params, 1, 2, 9)];
+        let inflated_coms: Vec<_> = inflated.iter().map(|r| r.blinded_commitment()).collect();
+        assert!(!verify_balance(&params, &input_coms, &inflated_coms, AMOUNT, &proof));
+
+        // nor to other generators
+        let other_params = JZPedersenVectorParams::<4, P>::from_domain("other");
+        assert!(!verify_balance(&other_params, &input_coms, &output_coms, AMOUNT, &proof));
+    }
+}
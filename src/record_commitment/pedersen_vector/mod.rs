@@ -0,0 +1,132 @@
+pub mod constraints;
+pub mod balance;
+
+use ark_ec::{*, twisted_edwards::{Projective, TECurveConfig}};
+use ark_ff::*;
+use ark_std::{*, rand::Rng};
+
+use crate::utils;
+
+pub const DEFAULT_DOMAIN: &str = "jzexe.record_commitment.pedersen_vector";
+
+/// index under which the blinding generator is derived; record fields use 0..N
+const BLINDING_INDEX: u64 = u64::MAX;
+
+#[derive(Derivative)]
+#[derivative(Clone(bound = "P: TECurveConfig"))]
+pub struct JZPedersenVectorParams<const N: usize, P: TECurveConfig> {
+    /// one generator per record field
+    pub generators: Vec<Projective<P>>,
+    pub blinding_generator: Projective<P>,
+}
+
+impl<const N: usize, P: TECurveConfig> JZPedersenVectorParams<N, P> {
+    /// derives all generators by hashing to the curve, so no one knows
+    /// discrete log relations between them
+    pub fn from_domain(domain: &str) -> Self {
+        let generators = (0..N)
+            .map(|i| utils::hash_to_curve::<P>(domain.as_bytes(), i as u64))
+            .collect();
+
+        let blinding_generator = utils::hash_to_curve::<P>(domain.as_bytes(), BLINDING_INDEX);
+
+        JZPedersenVectorParams { generators, blinding_generator }
+    }
+
+    /// nothing is sampled, the rng is only here for parity with the other record commitments
+    pub fn trusted_setup<R: Rng>(_rng: &mut R) -> Self {
+        Self::from_domain(DEFAULT_DOMAIN)
+    }
+}
+
+/// computes Σ values[i]·G_i + blind·H; commitments add up to the
+/// commitment of the summed values and blinds
+pub fn commit<const N: usize, P: TECurveConfig>(
+    params: &JZPedersenVectorParams<N, P>,
+    values: &[P::ScalarField; N],
+    blind: &P::ScalarField
+) -> Projective<P> {
+    let mut acc = params.blinding_generator.mul_bigint(blind.into_bigint());
+    for (generator, value) in params.generators.iter().zip(values.iter()) {
+        acc += generator.mul_bigint(value.into_bigint());
+    }
+    acc
+}
+
+/// JZRecord<N,P> where N is the number of fields and P is the Edwards curve
+#[derive(Derivative)]
+#[derivative(Clone(bound = "P: TECurveConfig"))]
+pub struct JZRecord<const N: usize, P: TECurveConfig> {
+    pub params: JZPedersenVectorParams<N, P>,
+    pub fields: [Vec<u8>; N], //Nth field is the entropy
+    pub blind: Vec<u8>, //in case we want to reveal a blinded commitment
+}
+
+impl<const N: usize, P: TECurveConfig> JZRecord<N, P> {
+    pub fn new(
+        params: &JZPedersenVectorParams<N, P>,
+        fields: &[Vec<u8>; N],
+        blind: &Vec<u8>
+    ) -> Self {
+        JZRecord {
+            params: params.clone(),
+            fields: fields.to_owned(),
+            blind: blind.to_owned(),
+        }
+    }
+
+    pub fn commitment(&self) -> Projective<P> {
+        commit(&self.params, &self.fields(), &P::ScalarField::zero())
+    }
+
+    pub fn blinded_commitment(&self) -> Projective<P> {
+        commit(&self.params, &self.fields(), &self.blind())
+    }
+
+    /// each field read as a little-endian integer, reduced modulo the group order
+    pub fn fields(&self) -> [P::ScalarField; N] {
+        self.fields
+            .iter()
+            .map(|field| P::ScalarField::from_le_bytes_mod_order(field))
+            .collect::<Vec<P::ScalarField>>()
+            .try_into()
+            .unwrap()
+    }
+
+    pub fn blind(&self) -> P::ScalarField {
+        P::ScalarField::from_le_bytes_mod_order(&self.blind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type P = ark_ed_on_bls12_377::EdwardsConfig;
+
+    #[test]
+    fn test_pedersen_vector_homomorphism() {
+        let params = JZPedersenVectorParams::<4, P>::from_domain(DEFAULT_DOMAIN);
+
+        // generators are deterministic, distinct and in the prime-order subgroup
+        let again = JZPedersenVectorParams::<4, P>::from_domain(DEFAULT_DOMAIN);
+        assert_eq!(params.generators, again.generators);
+        for (i, g) in params.generators.iter().enumerate() {
+            assert!(g.into_affine().is_in_correct_subgroup_assuming_on_curve());
+            assert_ne!(*g, params.blinding_generator);
+            assert!(params.generators[i + 1..].iter().all(|h| h != g));
+        }
+
+        let input_a = JZRecord::<4, P>::new(&params, &[vec![1u8; 31], vec![2u8], vec![40u8], vec![10u8]], &vec![5u8; 31]);
+        let input_b = JZRecord::<4, P>::new(&params, &[vec![3u8; 31], vec![2u8], vec![40u8], vec![15u8]], &vec![6u8; 31]);
+
+        // adding commitments adds the committed amounts (index 3) and blinds
+        let sum = input_a.blinded_commitment() + input_b.blinded_commitment();
+        let mut summed_fields = input_a.fields();
+        for (x, y) in summed_fields.iter_mut().zip(input_b.fields().iter()) {
+            *x += y;
+        }
+        assert_eq!(summed_fields[3], <P as CurveConfig>::ScalarField::from(25u64));
+        assert_eq!(sum, commit(&params, &summed_fields, &(input_a.blind() + input_b.blind())));
+    }
+}
@@ -0,0 +1,150 @@
+use ark_crypto_primitives::crh::{CRHSchemeGadget, poseidon::constraints::*};
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ff::*;
+use ark_relations::r1cs::*;
+use ark_r1cs_std::prelude::*;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_std::{borrow::*, *};
+
+use super::{JZRecord, JZPoseidonCommitmentParams, Hash};
+
+pub struct JZPoseidonCommitmentParamsVar<F: PrimeField + Absorb> {
+    pub poseidon_params_var: CRHParametersVar<F>,
+}
+
+impl<F: PrimeField + Absorb> AllocVar<JZPoseidonCommitmentParams<F>, F> for JZPoseidonCommitmentParamsVar<F> {
+    fn new_variable<T: Borrow<JZPoseidonCommitmentParams<F>>>(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<T>,
+        mode: AllocationMode
+    ) -> Result<Self> {
+        f().and_then(|val| {
+            let cs = cs.into();
+
+            let poseidon_params_var = CRHParametersVar::<F>::new_variable(
+                cs.clone(),
+                || Ok(&val.borrow().poseidon_params),
+                mode
+            )?;
+
+            Ok(JZPoseidonCommitmentParamsVar { poseidon_params_var })
+        })
+    }
+}
+
+/// the fields are allocated directly as field elements of the circuit,
+/// so the record must live in the circuit's own scalar field
+pub struct JZRecordVar<const N: usize, F: PrimeField> {
+    pub fields: [FpVar<F>; N],
+    pub blind: FpVar<F>,
+    pub commitment: FpVar<F>,
+    pub blinded_commitment: FpVar<F>,
+}
+
+impl<const N: usize, const M: usize, F: PrimeField + Absorb + std::convert::From<BigInt<M>>>
+AllocVar<JZRecord<N, M, F>, F> for JZRecordVar<N, F> {
+    fn new_variable<T: Borrow<JZRecord<N, M, F>>>(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<T>,
+        mode: AllocationMode
+    ) -> Result<Self> {
+        f().and_then(|val| {
+            let cs = cs.into();
+            let record: &JZRecord<N, M, F> = val.borrow();
+
+            let mut field_vars = vec![];
+            for field in record.fields().iter() {
+                field_vars.push(FpVar::<F>::new_variable(cs.clone(), || Ok(field), mode)?);
+            }
+
+            let blind = FpVar::<F>::new_variable(
+                cs.clone(),
+                || Ok(crate::record_commitment::schema::to_field::<F>(&record.blind)),
+                mode
+            )?;
+
+            let commitment = FpVar::<F>::new_variable(
+                cs.clone(),
+                || Ok(record.commitment()),
+                mode
+            )?;
+
+            let blinded_commitment = FpVar::<F>::new_variable(
+                cs.clone(),
+                || Ok(record.blinded_commitment()),
+                mode
+            )?;
+
+            Ok(
+                JZRecordVar {
+                    fields: field_vars.try_into().unwrap(),
+                    blind,
+                    commitment,
+                    blinded_commitment
+                }
+            )
+        })
+    }
+}
+
+pub fn generate_constraints<const N: usize, F: PrimeField + Absorb>(
+    _cs: ConstraintSystemRef<F>,
+    params: &JZPoseidonCommitmentParamsVar<F>,
+    record: &JZRecordVar<N, F>
+) -> Result<()> {
+
+    let computed_com = <CRHGadget<F> as CRHSchemeGadget<Hash<F>, F>>::evaluate(
+        &params.poseidon_params_var,
+        &record.fields
+    )?;
+
+    record.commitment.enforce_equal(&computed_com)?;
+
+    //blinded commitment constraints
+    let mut blinded_fields = record.fields.clone();
+    blinded_fields[0] += &record.blind;
+
+    let computed_blinded_com = <CRHGadget<F> as CRHSchemeGadget<Hash<F>, F>>::evaluate(
+        &params.poseidon_params_var,
+        &blinded_fields
+    )?;
+
+    record.blinded_commitment.enforce_equal(&computed_blinded_com)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::test_rng;
+
+    type F = ark_bls12_377::Fr;
+
+    #[test]
+    fn test_poseidon_com() {
+        let mut rng = test_rng();
+        let params = JZPoseidonCommitmentParams::<F>::trusted_setup(&mut rng);
+
+        let records: [Vec<u8>; 4] = [vec![20u8; 31], vec![244u8; 31], vec![40u8], vec![10u8]];
+        let coin = JZRecord::<4, 4, F>::new(&params, &records, &vec![7u8; 31]);
+        assert_ne!(coin.commitment(), coin.blinded_commitment());
+
+        let cs = ConstraintSystem::<F>::new_ref();
+        let params_var = JZPoseidonCommitmentParamsVar::<F>::new_constant(cs.clone(), &params).unwrap();
+        let coin_var = JZRecordVar::<4, F>::new_witness(cs.clone(), || Ok(&coin)).unwrap();
+
+        generate_constraints(cs.clone(), &params_var, &coin_var).unwrap();
+        assert!(cs.is_satisfied().unwrap(), "constraints not satisfied");
+
+        // a different blind must not satisfy the same public commitment
+        let other = JZRecord::<4, 4, F>::new(&params, &records, &vec![8u8; 31]);
+        let cs = ConstraintSystem::<F>::new_ref();
+        let params_var = JZPoseidonCommitmentParamsVar::<F>::new_constant(cs.clone(), &params).unwrap();
+        let mut coin_var = JZRecordVar::<4, F>::new_witness(cs.clone(), || Ok(&other)).unwrap();
+        coin_var.blinded_commitment = FpVar::new_input(cs.clone(), || Ok(coin.blinded_commitment())).unwrap();
+
+        generate_constraints(cs.clone(), &params_var, &coin_var).unwrap();
+        assert!(!cs.is_satisfied().unwrap(), "wrong blind accepted");
+    }
+}
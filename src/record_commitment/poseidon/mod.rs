@@ -0,0 +1,74 @@
+pub mod constraints;
+
+use ark_crypto_primitives::crh::{CRHScheme, poseidon};
+use ark_crypto_primitives::sponge::{Absorb, poseidon::PoseidonConfig};
+use ark_std::{*, rand::Rng};
+use ark_ff::*;
+
+use crate::utils;
+use crate::record_commitment::schema::to_field;
+
+pub type Hash<F> = poseidon::CRH<F>;
+
+#[derive(Clone)]
+pub struct JZPoseidonCommitmentParams<F: PrimeField + Absorb> {
+    pub poseidon_params: PoseidonConfig<F>,
+}
+
+impl<F: PrimeField + Absorb> JZPoseidonCommitmentParams<F> {
+    /// the Poseidon constants are derived deterministically, so the rng is
+    /// not used; it is kept so this is a drop-in for the KZG params
+    pub fn trusted_setup<R: Rng>(_rng: &mut R) -> Self {
+        JZPoseidonCommitmentParams {
+            poseidon_params: utils::poseidon_parameters::<F>(2),
+        }
+    }
+}
+
+/// JZRecord<N,M,F> where N is the number of fields and M is the size of each field (in u64s)
+#[derive(Clone)]
+pub struct JZRecord<const N: usize, const M: usize, F: PrimeField + Absorb + std::convert::From<BigInt<M>>> {
+    pub params: JZPoseidonCommitmentParams<F>,
+    pub fields: [Vec<u8>; N], //Nth field is the entropy
+    pub blind: Vec<u8>, //in case we want to reveal a blinded commitment
+}
+
+impl<const N: usize, const M: usize, F: PrimeField + Absorb + std::convert::From<BigInt<M>>> JZRecord<N, M, F> {
+    pub fn new(
+        params: &JZPoseidonCommitmentParams<F>,
+        fields: &[Vec<u8>; N],
+        blind: &Vec<u8>
+    ) -> Self {
+        JZRecord {
+            params: params.clone(),
+            fields: fields.to_owned(),
+            blind: blind.to_owned(),
+        }
+    }
+
+    /// Poseidon hash of the N fields, each read as one field element
+    pub fn commitment(&self) -> F {
+        Hash::<F>::evaluate(&self.params.poseidon_params, self.fields().to_vec()).unwrap()
+    }
+
+    /// Poseidon hash of the blinded fields, where the blind is added to the entropy
+    pub fn blinded_commitment(&self) -> F {
+        Hash::<F>::evaluate(&self.params.poseidon_params, self.blinded_fields().to_vec()).unwrap()
+    }
+
+    /// each field as an element of F; panics unless they are canonical, see schema::to_field
+    pub fn fields(&self) -> [F; N] {
+        self.fields
+            .iter()
+            .map(|field| to_field::<F>(field))
+            .collect::<Vec<F>>()
+            .try_into()
+            .unwrap()
+    }
+
+    pub fn blinded_fields(&self) -> [F; N] {
+        let mut fields = self.fields();
+        fields[0] += to_field::<F>(&self.blind);
+        fields
+    }
+}
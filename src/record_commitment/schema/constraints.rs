@@ -0,0 +1,135 @@
+use ark_ff::*;
+use ark_relations::r1cs::*;
+use ark_r1cs_std::prelude::*;
+use ark_r1cs_std::bits::{uint8::UInt8, uint64::UInt64};
+
+use super::*;
+
+/// implemented by the record gadgets, so that schema gadgets can read their fields
+pub trait JZRecordFieldsVar<const N: usize, ConstraintF: PrimeField> {
+    fn field_vars(&self) -> &[Vec<UInt8<ConstraintF>>; N];
+}
+
+/// typed view over the field variables of a record gadget
+pub trait JZRecordSchemaVar<const N: usize, ConstraintF: PrimeField>: Sized {
+    fn from_field_vars(fields: &[Vec<UInt8<ConstraintF>>; N]) -> Result<Self>;
+
+    fn from_record_var<R: JZRecordFieldsVar<N, ConstraintF>>(record: &R) -> Result<Self> {
+        Self::from_field_vars(record.field_vars())
+    }
+}
+
+pub struct JZCoinVar<ConstraintF: PrimeField> {
+    pub entropy: Vec<UInt8<ConstraintF>>,
+    pub owner: Vec<UInt8<ConstraintF>>,
+    pub asset_id: UInt64<ConstraintF>,
+    pub amount: UInt64<ConstraintF>,
+    pub app_id: UInt64<ConstraintF>,
+    pub app_data: [UInt64<ConstraintF>; 2],
+    pub rho: Vec<UInt8<ConstraintF>>,
+}
+
+fn bytes_var<ConstraintF: PrimeField>(
+    bytes: &[UInt8<ConstraintF>],
+    expected: usize
+) -> Result<Vec<UInt8<ConstraintF>>> {
+    // the length of a field is fixed at allocation time, so a mismatch
+    // means the record was built against a different schema
+    if bytes.len() != expected {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+    Ok(bytes.to_vec())
+}
+
+fn u64_var<ConstraintF: PrimeField>(
+    bytes: &[UInt8<ConstraintF>]
+) -> Result<UInt64<ConstraintF>> {
+    let bytes = bytes_var(bytes, U64_LEN)?;
+    Ok(UInt64::from_bits_le(&bytes.to_bits_le()?))
+}
+
+impl<ConstraintF: PrimeField> JZRecordSchemaVar<8, ConstraintF> for JZCoinVar<ConstraintF> {
+    fn from_field_vars(fields: &[Vec<UInt8<ConstraintF>>; 8]) -> Result<Self> {
+        Ok(JZCoinVar {
+            entropy: bytes_var(&fields[ENTROPY], ENTROPY_LEN)?,
+            owner: bytes_var(&fields[OWNER], KEY_LEN)?,
+            asset_id: u64_var(&fields[ASSET_ID])?,
+            amount: u64_var(&fields[AMOUNT])?,
+            app_id: u64_var(&fields[APP_ID])?,
+            app_data: [
+                u64_var(&fields[APP_DATA_0])?,
+                u64_var(&fields[APP_DATA_1])?,
+            ],
+            rho: bytes_var(&fields[RHO], KEY_LEN)?,
+        })
+    }
+}
+
+/// enforces that the little-endian bytes encode an integer below RecordF's modulus
+pub fn enforce_canonical<RecordF: PrimeField, ConstraintF: PrimeField>(
+    bytes: &[UInt8<ConstraintF>]
+) -> Result<()> {
+    codec::constraints::enforce_canonical::<RecordF, ConstraintF>(bytes, JZEndianness::Little)
+}
+
+/// enforces canonical encodings for all fields of the record
+pub fn generate_constraints<const N: usize, RecordF: PrimeField, ConstraintF: PrimeField>(
+    _cs: ConstraintSystemRef<ConstraintF>,
+    record: &impl JZRecordFieldsVar<N, ConstraintF>
+) -> Result<()> {
+    for field in record.field_vars().iter() {
+        enforce_canonical::<RecordF, ConstraintF>(field)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::record_commitment::kzg::{JZRecord, JZKZGCommitmentParams, constraints::JZRecordVar};
+    use ark_std::test_rng;
+
+    type C = ark_bls12_377::Config;
+
+    #[test]
+    fn test_coin_var() {
+        let mut rng = test_rng();
+        let crs = JZKZGCommitmentParams::<8, 4, C>::trusted_setup(&mut rng);
+
+        let coin = JZCoin {
+            entropy: [3u8; ENTROPY_LEN],
+            owner: [7u8; KEY_LEN],
+            asset_id: 40,
+            amount: 10,
+            app_id: 1,
+            app_data: [0, 0],
+            rho: [9u8; KEY_LEN],
+        };
+        let record = JZRecord::<8, 4, C>::from_schema(&crs, &coin, &vec![1u8; ENTROPY_LEN]).unwrap();
+
+        let cs = ConstraintSystem::<ark_bw6_761::Fr>::new_ref();
+        let record_var = JZRecordVar::<8, C, ark_bls12_377::Fq>::new_witness(
+            cs.clone(), || Ok(&record)
+        ).unwrap();
+
+        let coin_var = JZCoinVar::from_record_var(&record_var).unwrap();
+        assert_eq!(coin_var.amount.value().unwrap(), 10);
+        assert_eq!(coin_var.asset_id.value().unwrap(), 40);
+
+        generate_constraints::<8, ark_bls12_377::Fr, _>(cs.clone(), &record_var).unwrap();
+        assert!(cs.is_satisfied().unwrap(), "constraints not satisfied");
+
+        // an owner key at the modulus must be rejected
+        let mut fields = coin.to_fields();
+        fields[OWNER] = ark_bls12_377::Fr::MODULUS.to_bytes_le();
+        let record = JZRecord::<8, 4, C>::new(&crs, &fields, &vec![1u8; ENTROPY_LEN]);
+
+        let cs = ConstraintSystem::<ark_bw6_761::Fr>::new_ref();
+        let record_var = JZRecordVar::<8, C, ark_bls12_377::Fq>::new_witness(
+            cs.clone(), || Ok(&record)
+        ).unwrap();
+
+        generate_constraints::<8, ark_bls12_377::Fr, _>(cs.clone(), &record_var).unwrap();
+        assert!(!cs.is_satisfied().unwrap(), "non-canonical owner accepted");
+    }
+}
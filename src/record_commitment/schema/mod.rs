@@ -0,0 +1,214 @@
+pub mod constraints;
+
+use ark_ff::*;
+use ark_std::*;
+
+use crate::codec::{self, JZEndianness};
+
+/// slot layout of a coin record
+pub const ENTROPY: usize = 0;
+pub const OWNER: usize = 1;
+pub const ASSET_ID: usize = 2;
+pub const AMOUNT: usize = 3;
+pub const APP_ID: usize = 4;
+pub const APP_DATA_0: usize = 5;
+pub const APP_DATA_1: usize = 6;
+pub const RHO: usize = 7;
+
+/// 31 bytes always fit below the ~253 bit scalar fields we use
+pub const ENTROPY_LEN: usize = 31;
+pub const KEY_LEN: usize = 32;
+pub const U64_LEN: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JZSchemaError {
+    /// the field does not have the byte length the schema expects
+    InvalidLength { field: &'static str, expected: usize, actual: usize },
+    /// the field, read as a little-endian integer, is not below the scalar field modulus
+    NonCanonical { field: &'static str },
+    /// a runtime-sized record has a different number of fields than the schema
+    ArityMismatch { expected: usize, actual: usize },
+    /// a runtime-sized record has no fields, not even the entropy
+    NoFields,
+    /// a runtime-sized record has more fields than the CRS has slots
+    TooManyFields { capacity: usize, actual: usize },
+    /// a packing layout does not place every field once within slot capacity
+    InvalidLayout { reason: &'static str },
+}
+
+/// a typed view over the N byte fields of a record; encodings are
+/// little-endian, matching how JZRecord::fields() interprets the bytes
+pub trait JZRecordSchema<const N: usize>: Sized {
+    /// names of the N fields, in slot order
+    const FIELD_NAMES: [&'static str; N];
+
+    fn to_fields(&self) -> [Vec<u8>; N];
+
+    fn from_fields(fields: &[Vec<u8>; N]) -> Result<Self, JZSchemaError>;
+
+    /// encodes to record fields, rejecting values that would wrap around F's modulus
+    fn encode<F: PrimeField>(&self) -> Result<[Vec<u8>; N], JZSchemaError> {
+        let fields = self.to_fields();
+        check_canonical::<F, N>(&Self::FIELD_NAMES, &fields)?;
+        Ok(fields)
+    }
+
+    /// decodes record fields, rejecting non-canonical encodings
+    fn decode<F: PrimeField>(fields: &[Vec<u8>; N]) -> Result<Self, JZSchemaError> {
+        check_canonical::<F, N>(&Self::FIELD_NAMES, fields)?;
+        Self::from_fields(fields)
+    }
+}
+
+/// true iff bytes, read as a little-endian integer, is smaller than F's modulus
+pub fn is_canonical<F: PrimeField>(bytes: &[u8]) -> bool {
+    codec::is_canonical::<F>(bytes, JZEndianness::Little)
+}
+
+/// bytes read as a little-endian field element, as JZRecord::fields() does;
+/// panics on non-canonical bytes rather than reducing or truncating them,
+/// so records built from raw bytes should be checked with is_canonical
+pub fn to_field<F: PrimeField>(bytes: &[u8]) -> F {
+    codec::bytes_to_field_checked::<F>(bytes, JZEndianness::Little)
+        .expect("record bytes are not a canonical field element")
+}
+
+pub fn check_canonical<F: PrimeField, const N: usize>(
+    names: &[&'static str; N],
+    fields: &[Vec<u8>; N]
+) -> Result<(), JZSchemaError> {
+    for (name, field) in names.iter().zip(fields.iter()) {
+        if !is_canonical::<F>(field) {
+            return Err(JZSchemaError::NonCanonical { field: name });
+        }
+    }
+    Ok(())
+}
+
+/// the fields of a runtime-sized record, as the fixed-size array a schema decodes
+pub fn fields_array<const N: usize>(fields: &[Vec<u8>]) -> Result<[Vec<u8>; N], JZSchemaError> {
+    fields
+        .to_vec()
+        .try_into()
+        .map_err(|_| JZSchemaError::ArityMismatch { expected: N, actual: fields.len() })
+}
+
+fn check_length(
+    field: &'static str,
+    bytes: &[u8],
+    expected: usize
+) -> Result<(), JZSchemaError> {
+    if bytes.len() != expected {
+        return Err(JZSchemaError::InvalidLength { field, expected, actual: bytes.len() });
+    }
+    Ok(())
+}
+
+pub fn encode_u64(value: u64) -> Vec<u8> {
+    value.to_le_bytes().to_vec()
+}
+
+pub fn decode_u64(field: &'static str, bytes: &[u8]) -> Result<u64, JZSchemaError> {
+    check_length(field, bytes, U64_LEN)?;
+    Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+pub fn decode_bytes<const L: usize>(
+    field: &'static str,
+    bytes: &[u8]
+) -> Result<[u8; L], JZSchemaError> {
+    check_length(field, bytes, L)?;
+    Ok(bytes.try_into().unwrap())
+}
+
+/// the 8-field coin record used by the payment apps
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JZCoin {
+    pub entropy: [u8; ENTROPY_LEN],
+    pub owner: [u8; KEY_LEN],
+    pub asset_id: u64,
+    pub amount: u64,
+    pub app_id: u64,
+    pub app_data: [u64; 2],
+    pub rho: [u8; KEY_LEN],
+}
+
+impl JZRecordSchema<8> for JZCoin {
+    const FIELD_NAMES: [&'static str; 8] = [
+        "entropy", "owner", "asset_id", "amount", "app_id", "app_data_0", "app_data_1", "rho"
+    ];
+
+    fn to_fields(&self) -> [Vec<u8>; 8] {
+        [
+            self.entropy.to_vec(),
+            self.owner.to_vec(),
+            encode_u64(self.asset_id),
+            encode_u64(self.amount),
+            encode_u64(self.app_id),
+            encode_u64(self.app_data[0]),
+            encode_u64(self.app_data[1]),
+            self.rho.to_vec(),
+        ]
+    }
+
+    fn from_fields(fields: &[Vec<u8>; 8]) -> Result<Self, JZSchemaError> {
+        let names = Self::FIELD_NAMES;
+        Ok(JZCoin {
+            entropy: decode_bytes::<ENTROPY_LEN>(names[ENTROPY], &fields[ENTROPY])?,
+            owner: decode_bytes::<KEY_LEN>(names[OWNER], &fields[OWNER])?,
+            asset_id: decode_u64(names[ASSET_ID], &fields[ASSET_ID])?,
+            amount: decode_u64(names[AMOUNT], &fields[AMOUNT])?,
+            app_id: decode_u64(names[APP_ID], &fields[APP_ID])?,
+            app_data: [
+                decode_u64(names[APP_DATA_0], &fields[APP_DATA_0])?,
+                decode_u64(names[APP_DATA_1], &fields[APP_DATA_1])?,
+            ],
+            rho: decode_bytes::<KEY_LEN>(names[RHO], &fields[RHO])?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type F = ark_bls12_377::Fr;
+
+    fn coin() -> JZCoin {
+        JZCoin {
+            entropy: [3u8; ENTROPY_LEN],
+            owner: [7u8; KEY_LEN],
+            asset_id: 40,
+            amount: 10,
+            app_id: 0,
+            app_data: [0, 0],
+            rho: [9u8; KEY_LEN],
+        }
+    }
+
+    #[test]
+    fn test_coin_schema() {
+        let fields = coin().encode::<F>().unwrap();
+        assert_eq!(JZCoin::decode::<F>(&fields).unwrap(), coin());
+
+        // an owner key above the modulus would silently wrap in fields()
+        let mut bad_coin = coin();
+        bad_coin.owner = [255u8; KEY_LEN];
+        assert_eq!(
+            bad_coin.encode::<F>(),
+            Err(JZSchemaError::NonCanonical { field: "owner" })
+        );
+
+        let mut bad_fields = fields.clone();
+        bad_fields[AMOUNT] = vec![10u8];
+        assert_eq!(
+            JZCoin::decode::<F>(&bad_fields),
+            Err(JZSchemaError::InvalidLength { field: "amount", expected: U64_LEN, actual: 1 })
+        );
+
+        // modulus - 1 is the largest canonical value, the modulus itself is not
+        let max = (-F::one()).into_bigint().to_bytes_le();
+        assert!(is_canonical::<F>(&max));
+        assert!(!is_canonical::<F>(&F::MODULUS.to_bytes_le()));
+    }
+}
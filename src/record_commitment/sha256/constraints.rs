@@ -6,13 +6,16 @@ use ark_r1cs_std::{bits::uint8::UInt8, prelude::*};
 use ark_std::{borrow::*, *};
 use ark_crypto_primitives::crh::sha256::{*, constraints::*};
 
-use super::JZRecord;
+use super::{JZRecord, JZDynRecord, COMMITMENT_DOMAIN, COMMITMENT_VERSION, BLINDED_ENTROPY_DOMAIN};
+use crate::record_commitment::schema::constraints::JZRecordFieldsVar;
 
 pub struct JZRecordVar<const N: usize, ConstraintF>
     where ConstraintF: PrimeField
 {
     pub fields: [Vec<UInt8<ConstraintF>>; N],
+    pub blind: Vec<UInt8<ConstraintF>>,
     pub commitment: DigestVar<ConstraintF>,
+    pub blinded_commitment: DigestVar<ConstraintF>,
 }
 
 impl<const N: usize, const M: usize, RecordF: PrimeField + std::convert::From<BigInt<M>>, ConstraintF: PrimeField>
@@ -39,26 +42,15 @@ AllocVar<JZRecord<N, M, RecordF>, ConstraintF> for JZRecordVar<N, ConstraintF> {
                 byte_vars.push(field_byte_vars);
             }
 
-            // lets create a new record with blinded randomness
-            // let blinded_randomness: Vec<u8> = fields[0]
-            //     .iter()
-            //     .zip(val.borrow().blind.iter())
-            //     .map(|(&a, &b)| a + b)
-            //     .collect();
-            // let mut blinded_fields = fields.clone();
-            // blinded_fields[0] = blinded_randomness;
-
-            // let mut blinded_byte_vars: Vec<UInt8<ConstraintF>> = vec![];
-            // for i in 0..N {
-            //     // allocate a byte var for each byte within each of the N fields
-            //     for byte in blinded_fields[i].as_slice() {
-            //         blinded_byte_vars.push(UInt8::<ConstraintF>::new_variable(
-            //             cs.clone(), || Ok(byte), mode)?);
-            //     }
-            // }
+            let mut blind_byte_vars = vec![];
+            for byte in val.borrow().blind.iter() {
+                blind_byte_vars.push(UInt8::<ConstraintF>::new_variable(
+                    cs.clone(), || Ok(byte), mode
+                )?);
+            }
 
             let sha256_com: Vec<u8> = val.borrow().commitment();
-            //let blinded_sha256_com = val.borrow().blinded_commitment();
+            let blinded_sha256_com: Vec<u8> = val.borrow().blinded_commitment();
 
             let sha256_digest = DigestVar::new_variable(
                 cs.clone(),
@@ -66,41 +58,197 @@ AllocVar<JZRecord<N, M, RecordF>, ConstraintF> for JZRecordVar<N, ConstraintF> {
                 mode
             )?;
 
-            // let blinded_sha256_digest = DigestVar::new_variable(
-            //     cs.clone(),
-            //     || Ok(blinded_sha256_com),
-            //     mode
-            // )?;
+            let blinded_sha256_digest = DigestVar::new_variable(
+                cs.clone(),
+                || Ok(blinded_sha256_com),
+                mode
+            )?;
 
             Ok(
                 JZRecordVar {
                     fields: byte_vars.try_into().unwrap(),
-                    //blind: blinded_byte_vars,
+                    blind: blind_byte_vars,
                     commitment: sha256_digest,
-                    //blinded_commitment: blinded_sha256_digest
+                    blinded_commitment: blinded_sha256_digest
+                }
+            )
+        })
+    }
+}
+
+pub struct JZDynRecordVar<ConstraintF>
+    where ConstraintF: PrimeField
+{
+    pub fields: Vec<Vec<UInt8<ConstraintF>>>,
+    pub blind: Vec<UInt8<ConstraintF>>,
+    pub commitment: DigestVar<ConstraintF>,
+    pub blinded_commitment: DigestVar<ConstraintF>,
+}
+
+impl<const M: usize, RecordF: PrimeField + std::convert::From<BigInt<M>>, ConstraintF: PrimeField>
+AllocVar<JZDynRecord<M, RecordF>, ConstraintF> for JZDynRecordVar<ConstraintF> {
+    fn new_variable<T: Borrow<JZDynRecord<M, RecordF>>>(
+        cs: impl Into<Namespace<ConstraintF>>,
+        f: impl FnOnce() -> Result<T>,
+        mode: AllocationMode
+    ) -> Result<Self> {
+        f().and_then(|val| {
+            let cs = cs.into();
+            let record: &JZDynRecord<M, RecordF> = val.borrow();
+            if record.fields.is_empty() {
+                return Err(SynthesisError::Unsatisfiable);
+            }
+
+            let mut byte_vars: Vec<Vec<UInt8<ConstraintF>>> = vec![];
+            for field in record.fields.iter() {
+                let mut field_byte_vars = vec![];
+                for byte in field.iter() {
+                    field_byte_vars.push(UInt8::<ConstraintF>::new_variable(
+                        cs.clone(), || Ok(byte), mode
+                    )?);
+                }
+                byte_vars.push(field_byte_vars);
+            }
+
+            let mut blind_byte_vars = vec![];
+            for byte in record.blind.iter() {
+                blind_byte_vars.push(UInt8::<ConstraintF>::new_variable(
+                    cs.clone(), || Ok(byte), mode
+                )?);
+            }
+
+            let commitment = DigestVar::new_variable(
+                cs.clone(),
+                || Ok(record.commitment()),
+                mode
+            )?;
+
+            let blinded_commitment = DigestVar::new_variable(
+                cs.clone(),
+                || Ok(record.blinded_commitment()),
+                mode
+            )?;
+
+            Ok(
+                JZDynRecordVar {
+                    fields: byte_vars,
+                    blind: blind_byte_vars,
+                    commitment,
+                    blinded_commitment
                 }
             )
         })
     }
 }
 
+impl<const N: usize, ConstraintF: PrimeField> JZRecordFieldsVar<N, ConstraintF> for JZRecordVar<N, ConstraintF> {
+    fn field_vars(&self) -> &[Vec<UInt8<ConstraintF>>; N] {
+        &self.fields
+    }
+}
+
+fn constant_bytes<ConstraintF: PrimeField>(bytes: &[u8]) -> Vec<UInt8<ConstraintF>> {
+    bytes.iter().map(|&b| UInt8::constant(b)).collect()
+}
+
+/// the lengths are fixed at allocation time, so the prefixes are constants
+fn length_prefixed_var<ConstraintF: PrimeField>(
+    field: &[UInt8<ConstraintF>]
+) -> Vec<UInt8<ConstraintF>> {
+    let mut encoded = constant_bytes(&(field.len() as u32).to_le_bytes());
+    encoded.extend_from_slice(field);
+    encoded
+}
+
+/// in-circuit counterpart of super::encode_fields
+fn encode_fields_var<ConstraintF: PrimeField>(
+    fields: &[Vec<UInt8<ConstraintF>>]
+) -> Vec<UInt8<ConstraintF>> {
+    let mut encoded = constant_bytes(COMMITMENT_DOMAIN);
+    encoded.push(UInt8::constant(COMMITMENT_VERSION));
+    encoded.extend(constant_bytes(&(fields.len() as u32).to_le_bytes()));
+    for field in fields.iter() {
+        encoded.extend(length_prefixed_var(field));
+    }
+    encoded
+}
+
+fn sha256_var<ConstraintF: PrimeField>(
+    input: &[UInt8<ConstraintF>]
+) -> Result<DigestVar<ConstraintF>> {
+    <Sha256Gadget<ConstraintF> as CRHSchemeGadget<Sha256, ConstraintF>>::
+        evaluate(&UnitVar::default(), input)
+}
+
+/// enforces the record's commitment, at the cost of one SHA-256; the
+/// blinded commitment is only checked by generate_blinded_constraints
 pub fn generate_constraints<const N: usize, ConstraintF: PrimeField>(
     _cs: ConstraintSystemRef<ConstraintF>,
     record: &JZRecordVar<N, ConstraintF>
 ) -> Result<()> {
+    enforce_commitment(&record.fields, &record.commitment)
+}
 
-    let all_byte_vars: Vec<UInt8<ConstraintF>> = record.fields
-        .iter()
-        .flat_map(|v| v.iter().cloned())
-        .collect();
+/// enforces the record's blinded commitment, for circuits that reveal it;
+/// this costs two SHA-256s, one for the blinded entropy and one for the commitment
+pub fn generate_blinded_constraints<const N: usize, ConstraintF: PrimeField>(
+    _cs: ConstraintSystemRef<ConstraintF>,
+    record: &JZRecordVar<N, ConstraintF>
+) -> Result<()> {
+    enforce_blinded_commitment(&record.fields, &record.blind, &record.blinded_commitment)
+}
 
-    let computed_output = 
-        <Sha256Gadget<ConstraintF> as CRHSchemeGadget<Sha256, ConstraintF>>::
-            evaluate(&UnitVar::default(), &all_byte_vars)?;
+/// same as generate_constraints, for a record whose number of fields is
+/// only known at runtime; fails if the record does not have `num_fields`
+/// fields, or has none
+pub fn generate_dyn_constraints<ConstraintF: PrimeField>(
+    _cs: ConstraintSystemRef<ConstraintF>,
+    record: &JZDynRecordVar<ConstraintF>,
+    num_fields: usize
+) -> Result<()> {
+    if num_fields == 0 || record.fields.len() != num_fields {
+        return Err(SynthesisError::Unsatisfiable);
+    }
 
-    computed_output.enforce_equal(&record.commitment)?;
+    enforce_commitment(&record.fields, &record.commitment)
+}
+
+/// same as generate_blinded_constraints, with the checks of generate_dyn_constraints
+pub fn generate_dyn_blinded_constraints<ConstraintF: PrimeField>(
+    _cs: ConstraintSystemRef<ConstraintF>,
+    record: &JZDynRecordVar<ConstraintF>,
+    num_fields: usize
+) -> Result<()> {
+    if num_fields == 0 || record.fields.len() != num_fields {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+
+    enforce_blinded_commitment(&record.fields, &record.blind, &record.blinded_commitment)
+}
 
-    Ok(())
+fn enforce_commitment<ConstraintF: PrimeField>(
+    fields: &[Vec<UInt8<ConstraintF>>],
+    commitment: &DigestVar<ConstraintF>
+) -> Result<()> {
+    let computed_output = sha256_var(&encode_fields_var(fields))?;
+    computed_output.enforce_equal(commitment)
+}
+
+fn enforce_blinded_commitment<ConstraintF: PrimeField>(
+    fields: &[Vec<UInt8<ConstraintF>>],
+    blind: &[UInt8<ConstraintF>],
+    blinded_commitment: &DigestVar<ConstraintF>
+) -> Result<()> {
+    let mut entropy_preimage = constant_bytes(BLINDED_ENTROPY_DOMAIN);
+    entropy_preimage.extend(length_prefixed_var(&fields[0]));
+    entropy_preimage.extend(length_prefixed_var(blind));
+    let blinded_entropy = sha256_var(&entropy_preimage)?;
+
+    let mut blinded_fields = fields.to_vec();
+    blinded_fields[0] = blinded_entropy.to_bytes()?;
+
+    let computed_blinded_output = sha256_var(&encode_fields_var(&blinded_fields))?;
+    computed_blinded_output.enforce_equal(blinded_commitment)
 }
 
 #[cfg(test)]
@@ -124,6 +272,62 @@ mod tests {
         let coin_var = JZRecordVar::<4, ark_bw6_761::Fr>::new_witness(cs.clone(), || Ok(coin)).unwrap();
 
         generate_constraints(cs.clone(), &coin_var).unwrap();
+        let num_constraints = cs.num_constraints();
+        assert!(cs.is_satisfied().unwrap(), "constraints not satisfied");
+
+        // the blinded commitment is opt-in, and hashes twice more
+        generate_blinded_constraints(cs.clone(), &coin_var).unwrap();
+        assert!(cs.num_constraints() > 2 * num_constraints);
+        assert!(cs.is_satisfied().unwrap(), "constraints not satisfied");
+    }
+
+    #[test]
+    fn test_sha256_rerandomization() {
+        let mut rng = test_rng();
+
+        let records: [Vec<u8>; 4] = [vec![20u8; 31], vec![244u8; 31], vec![40u8], vec![10u8]];
+        let coin = JZRecord::<4, 4, ark_bls12_377::Fr>::new(&records, &vec![1u8; 31]);
+        let rerandomized = coin.rerandomize(&mut rng);
+        assert_ne!(coin.blinded_commitment(), rerandomized.blinded_commitment());
+
+        let cs = ConstraintSystem::<ark_bw6_761::Fr>::new_ref();
+        let coin_var = JZRecordVar::<4, ark_bw6_761::Fr>::new_witness(cs.clone(), || Ok(rerandomized)).unwrap();
+        generate_constraints(cs.clone(), &coin_var).unwrap();
+        generate_blinded_constraints(cs.clone(), &coin_var).unwrap();
+
+        // the re-randomized record still opens to the leaf of the original
+        let leaf_var = DigestVar::new_input(cs.clone(), || Ok(coin.commitment())).unwrap();
+        coin_var.commitment.enforce_equal(&leaf_var).unwrap();
+        assert!(cs.is_satisfied().unwrap(), "constraints not satisfied");
+    }
+
+    #[test]
+    fn test_sha256_dyn_com() {
+        let fields = vec![vec![20u8; 31], vec![244u8; 31], vec![40u8]];
+        let coin = JZDynRecord::<4, ark_bls12_377::Fr>::new(&fields, &vec![1u8; 31]).unwrap();
+        assert!(JZDynRecord::<4, ark_bls12_377::Fr>::new(&[], &vec![1u8; 31]).is_err());
+
+        // same commitment as the fixed-arity record, but not as a padded one
+        let fixed = JZRecord::<3, 4, ark_bls12_377::Fr>::new(&fields.clone().try_into().unwrap(), &vec![1u8; 31]);
+        assert_eq!(coin.blinded_commitment(), fixed.blinded_commitment());
+        let mut padded = fields.clone();
+        padded.push(vec![]);
+        assert_ne!(coin.commitment(), JZDynRecord::<4, ark_bls12_377::Fr>::new(&padded, &vec![1u8; 31]).unwrap().commitment());
+
+        let cs = ConstraintSystem::<ark_bw6_761::Fr>::new_ref();
+        let coin_var = JZDynRecordVar::<ark_bw6_761::Fr>::new_witness(cs.clone(), || Ok(&coin)).unwrap();
+
+        assert!(generate_dyn_constraints(cs.clone(), &coin_var, 4).is_err());
+        generate_dyn_constraints(cs.clone(), &coin_var, 3).unwrap();
+        generate_dyn_blinded_constraints(cs.clone(), &coin_var, 3).unwrap();
         assert!(cs.is_satisfied().unwrap(), "constraints not satisfied");
+
+        // a record without fields has no entropy to blind
+        let empty = JZDynRecord::<4, ark_bls12_377::Fr> { fields: vec![], blind: vec![1u8; 31], _phantom: marker::PhantomData };
+        assert!(JZDynRecordVar::<ark_bw6_761::Fr>::new_witness(cs.clone(), || Ok(&empty)).is_err());
+
+        let empty_var = JZDynRecordVar { fields: vec![], ..coin_var };
+        assert!(generate_dyn_constraints(cs.clone(), &empty_var, 0).is_err());
+        assert!(generate_dyn_blinded_constraints(cs.clone(), &empty_var, 0).is_err());
     }
-}
\ No newline at end of file
+}
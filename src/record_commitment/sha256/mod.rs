@@ -4,16 +4,58 @@ use ark_crypto_primitives::crh::{CRHScheme, sha256::*};
 use ark_std::{*, borrow::*};
 use ark_ff::*;
 use ark_std::marker::PhantomData;
+use ark_std::rand::Rng;
 
-use crate::utils;
+use crate::record_commitment::schema::*;
 
-fn hash_of_fields(fields: &[Vec<u8>]) -> Vec<u8> {
-    let mut concatenated = Vec::new();
+/// domain tag of the record commitment; bump COMMITMENT_VERSION whenever the encoding changes
+pub const COMMITMENT_DOMAIN: &[u8] = b"jzrecord.sha256.commitment";
+pub const COMMITMENT_VERSION: u8 = 1;
+
+/// domain tag for deriving blinded entropy from the entropy field and the blind
+pub const BLINDED_ENTROPY_DOMAIN: &[u8] = b"jzrecord.sha256.blinded_entropy";
+
+/// prefixes a field with its length as a 4-byte little-endian integer
+pub fn length_prefixed(field: &[u8]) -> Vec<u8> {
+    let mut encoded = (field.len() as u32).to_le_bytes().to_vec();
+    encoded.extend_from_slice(field);
+    encoded
+}
+
+/// DOMAIN || VERSION || N || len(f_0) || f_0 || ... || len(f_{N-1}) || f_{N-1},
+/// with all lengths as 4-byte little-endian integers, so that no two
+/// distinct field vectors share a preimage
+pub fn encode_fields(fields: &[Vec<u8>]) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    encoded.extend_from_slice(COMMITMENT_DOMAIN);
+    encoded.push(COMMITMENT_VERSION);
+    encoded.extend_from_slice(&(fields.len() as u32).to_le_bytes());
     for field in fields.iter() {
-        concatenated.extend_from_slice(field);
+        encoded.extend_from_slice(&length_prefixed(field));
     }
+    encoded
+}
 
-    Sha256::evaluate(&(), concatenated).unwrap()
+fn hash_of_fields(fields: &[Vec<u8>]) -> Vec<u8> {
+    Sha256::evaluate(&(), encode_fields(fields)).unwrap()
+}
+
+fn blinded_hash_of_fields(fields: &[Vec<u8>], blind: &[u8]) -> Vec<u8> {
+    // replace the entropy (index 0) by a hash of the entropy and the blind
+    let mut new_fields = fields.to_vec();
+    new_fields[0] = blinded_entropy(&fields[0], blind);
+
+    hash_of_fields(&new_fields)
+}
+
+/// H(BLINDED_ENTROPY_DOMAIN || len(entropy) || entropy || len(blind) || blind)
+pub fn blinded_entropy(entropy: &[u8], blind: &[u8]) -> Vec<u8> {
+    let mut preimage = Vec::new();
+    preimage.extend_from_slice(BLINDED_ENTROPY_DOMAIN);
+    preimage.extend_from_slice(&length_prefixed(entropy));
+    preimage.extend_from_slice(&length_prefixed(blind));
+
+    Sha256::evaluate(&(), preimage).unwrap()
 }
 
 #[derive(Clone)]
@@ -28,10 +70,6 @@ impl<const N: usize, const M: usize, RecordF: PrimeField + std::convert::From<Bi
         fields: &[Vec<u8>; N],
         blind: &Vec<u8>
     ) -> Self {
-        assert!(
-            fields[0].len() == blind.len(), 
-            "Blind and entropy field (index 0) must have the same length"
-        );
         JZRecord::<N, M, RecordF> {
             fields: fields.to_owned(),
             blind: blind.to_owned(),
@@ -39,36 +77,153 @@ impl<const N: usize, const M: usize, RecordF: PrimeField + std::convert::From<Bi
         }
     }
 
+    /// builds a record from a typed schema value, checking the encoding is canonical
+    pub fn from_schema<S: JZRecordSchema<N>>(
+        value: &S,
+        blind: &Vec<u8>
+    ) -> Result<Self, JZSchemaError> {
+        let fields = value.encode::<RecordF>()?;
+        Ok(Self::new(&fields, blind))
+    }
+
+    pub fn to_schema<S: JZRecordSchema<N>>(&self) -> Result<S, JZSchemaError> {
+        S::decode::<RecordF>(&self.fields)
+    }
+
     pub fn commitment(&self) -> Vec<u8> {
         hash_of_fields(&self.fields)
     }
 
     pub fn blinded_commitment(&self) -> Vec<u8> {
-        // set the randomness to be the sum of current entropy and blind value
-        let new_randomness: Vec<u8> = self.fields[0]
-            .iter()
-            .zip(self.blind.iter())
-            .map(|(&a, &b)| a + b)
-            .collect();
+        blinded_hash_of_fields(&self.fields, &self.blind)
+    }
 
-        let mut new_fields = self.fields.clone();
-        new_fields[0] = new_randomness;
+    /// the same record under a fresh blind, so its blinded commitment is
+    /// unlinkable to ours. SHA-256 commitments are not homomorphic, so there
+    /// is no native proof relating the two; generate_constraints and
+    /// generate_blinded_constraints together prove that the new blinded
+    /// commitment opens to the same fields as the tree leaf
+    pub fn rerandomize<R: Rng>(&self, rng: &mut R) -> Self {
+        let mut blind = vec![0u8; cmp::max(self.blind.len(), 32)];
+        rng.fill_bytes(&mut blind);
 
-        hash_of_fields(&new_fields)
+        Self::new(&self.fields, &blind)
     }
 
     pub fn fields(&self) -> [RecordF; N] {
         self.fields
             .iter()
-            .map(|field| utils::bytes_to_field::<RecordF, M>(field))
+            .map(|field| to_field::<RecordF>(field))
             .collect::<Vec<RecordF>>()
             .try_into()
             .unwrap()
     }
 
+    /// the fields under blinded_commitment(): the entropy slot holds
+    /// blinded_entropy(entropy, blind), reduced since a SHA-256 digest can
+    /// exceed the modulus
     pub fn blinded_fields(&self) -> [RecordF; N] {
         let mut fields = self.fields();
-        fields[0] += utils::bytes_to_field::<RecordF, M>(&self.blind);
+        fields[0] = RecordF::from_le_bytes_mod_order(&blinded_entropy(&self.fields[0], &self.blind));
+        fields
+    }
+}
+
+/// JZRecord with its number of fields chosen at runtime; the field count
+/// is part of the encoding, so records of different arities never collide
+#[derive(Clone)]
+pub struct JZDynRecord<const M: usize, RecordF: PrimeField + std::convert::From<BigInt<M>>> {
+    pub fields: Vec<Vec<u8>>, //first field is the entropy
+    pub blind: Vec<u8>, //in case we want to reveal a blinded commitment
+    pub _phantom: PhantomData<RecordF>
+}
+
+impl<const M: usize, RecordF: PrimeField + std::convert::From<BigInt<M>>> JZDynRecord<M, RecordF> {
+    /// fails if there are no fields, since the first one is the entropy
+    pub fn new(
+        fields: &[Vec<u8>],
+        blind: &Vec<u8>
+    ) -> Result<Self, JZSchemaError> {
+        if fields.is_empty() {
+            return Err(JZSchemaError::NoFields);
+        }
+
+        Ok(JZDynRecord::<M, RecordF> {
+            fields: fields.to_vec(),
+            blind: blind.to_owned(),
+            _phantom: PhantomData
+        })
+    }
+
+    /// builds a record with the schema's arity, checking the encoding is canonical
+    pub fn from_schema<const N: usize, S: JZRecordSchema<N>>(
+        value: &S,
+        blind: &Vec<u8>
+    ) -> Result<Self, JZSchemaError> {
+        let fields = value.encode::<RecordF>()?;
+        Self::new(&fields, blind)
+    }
+
+    pub fn to_schema<const N: usize, S: JZRecordSchema<N>>(&self) -> Result<S, JZSchemaError> {
+        S::decode::<RecordF>(&fields_array::<N>(&self.fields)?)
+    }
+
+    pub fn num_fields(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn commitment(&self) -> Vec<u8> {
+        hash_of_fields(&self.fields)
+    }
+
+    pub fn blinded_commitment(&self) -> Vec<u8> {
+        blinded_hash_of_fields(&self.fields, &self.blind)
+    }
+
+    /// see JZRecord::rerandomize
+    pub fn rerandomize<R: Rng>(&self, rng: &mut R) -> Self {
+        let mut blind = vec![0u8; cmp::max(self.blind.len(), 32)];
+        rng.fill_bytes(&mut blind);
+
+        JZDynRecord { fields: self.fields.clone(), blind, _phantom: PhantomData }
+    }
+
+    pub fn fields(&self) -> Vec<RecordF> {
+        self.fields
+            .iter()
+            .map(|field| to_field::<RecordF>(field))
+            .collect()
+    }
+
+    /// see JZRecord::blinded_fields
+    pub fn blinded_fields(&self) -> Vec<RecordF> {
+        let mut fields = self.fields();
+        fields[0] = RecordF::from_le_bytes_mod_order(&blinded_entropy(&self.fields[0], &self.blind));
         fields
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type RecordF = ark_bls12_377::Fr;
+
+    #[test]
+    fn test_sha256_com_encoding() {
+        // same concatenation, different field boundaries
+        let a = JZRecord::<2, 4, RecordF>::new(&[b"ab".to_vec(), b"c".to_vec()], &vec![0u8; 31]);
+        let b = JZRecord::<2, 4, RecordF>::new(&[b"a".to_vec(), b"bc".to_vec()], &vec![0u8; 31]);
+        assert_ne!(a.commitment(), b.commitment());
+
+        // blinding must not overflow on large entropy and blind bytes
+        let c = JZRecord::<2, 4, RecordF>::new(&[vec![255u8; 31], vec![1u8]], &vec![255u8; 31]);
+        assert_ne!(c.commitment(), c.blinded_commitment());
+
+        // the blinded fields are those under the blinded commitment
+        let entropy = blinded_entropy(&c.fields[0], &c.blind);
+        let blinded = JZRecord::<2, 4, RecordF>::new(&[entropy.clone(), vec![1u8]], &vec![]);
+        assert_eq!(c.blinded_commitment(), blinded.commitment());
+        assert_eq!(c.blinded_fields(), [RecordF::from_le_bytes_mod_order(&entropy), c.fields()[1]]);
+    }
+}
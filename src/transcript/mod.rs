@@ -0,0 +1,117 @@
+pub mod poseidon;
+
+use ark_ec::CurveGroup;
+use ark_ff::PrimeField;
+use blake2::{Blake2s256, Digest};
+
+/// Merlin-style Fiat–Shamir transcript over BLAKE2s.
+///
+/// Every message is absorbed as len(label) || label || len(message) ||
+/// message, with u64 LE lengths, so no two sequences of appends hash the
+/// same. The protocol domain is the first labelled message, and every
+/// challenge is absorbed back into the transcript, so later challenges
+/// depend on earlier ones.
+#[derive(Clone)]
+pub struct JZTranscript {
+    hasher: Blake2s256,
+}
+
+impl JZTranscript {
+    pub fn new(domain: &[u8]) -> Self {
+        let mut transcript = JZTranscript { hasher: Blake2s256::new() };
+        transcript.append_message(b"dom-sep", domain);
+        transcript
+    }
+
+    fn absorb(&mut self, bytes: &[u8]) {
+        self.hasher.update((bytes.len() as u64).to_le_bytes());
+        self.hasher.update(bytes);
+    }
+
+    pub fn append_message(&mut self, label: &[u8], message: &[u8]) {
+        self.absorb(label);
+        self.absorb(message);
+    }
+
+    pub fn append_u64(&mut self, label: &[u8], x: u64) {
+        self.append_message(label, &x.to_le_bytes());
+    }
+
+    /// points are absorbed in compressed form
+    pub fn append_point<G: CurveGroup>(&mut self, label: &[u8], point: &G) {
+        let mut bytes = vec![];
+        point.serialize_compressed(&mut bytes).unwrap();
+        self.append_message(label, &bytes);
+    }
+
+    pub fn append_scalar<F: PrimeField>(&mut self, label: &[u8], scalar: &F) {
+        let mut bytes = vec![];
+        scalar.serialize_compressed(&mut bytes).unwrap();
+        self.append_message(label, &bytes);
+    }
+
+    /// fills `dest` with output blocks H(state || counter), then absorbs
+    /// them so the next challenge is fresh
+    pub fn challenge_bytes(&mut self, label: &[u8], dest: &mut [u8]) {
+        self.append_message(label, &(dest.len() as u64).to_le_bytes());
+
+        for (counter, chunk) in dest.chunks_mut(32).enumerate() {
+            let mut block = self.hasher.clone();
+            block.update((counter as u64).to_le_bytes());
+            let digest = block.finalize();
+            chunk.copy_from_slice(&digest[..chunk.len()]);
+        }
+
+        self.append_message(b"challenge", dest);
+    }
+
+    /// reduces 128 bits more than the modulus, so the bias is below 2^-128
+    pub fn challenge_scalar<F: PrimeField>(&mut self, label: &[u8]) -> F {
+        let mut bytes = vec![0u8; (F::MODULUS_BIT_SIZE as usize + 128).div_ceil(8)];
+        self.challenge_bytes(label, &mut bytes);
+        F::from_le_bytes_mod_order(&bytes)
+    }
+
+    /// a 128-bit challenge, e.g. to embed in two scalar fields at once
+    pub fn challenge_u128(&mut self, label: &[u8]) -> u128 {
+        let mut bytes = [0u8; 16];
+        self.challenge_bytes(label, &mut bytes);
+        u128::from_le_bytes(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type F = ark_bls12_377::Fr;
+
+    #[test]
+    fn test_transcript() {
+        let mut transcript = JZTranscript::new(b"jzexe.transcript.test");
+        transcript.append_message(b"msg", b"hello");
+        let c: F = transcript.clone().challenge_scalar(b"c");
+
+        // deterministic
+        let mut again = JZTranscript::new(b"jzexe.transcript.test");
+        again.append_message(b"msg", b"hello");
+        assert_eq!(c, again.challenge_scalar::<F>(b"c"));
+
+        // the next challenge depends on the previous one
+        assert_ne!(c, again.challenge_scalar::<F>(b"c"));
+
+        // other domains, labels and message framings give other challenges
+        let mut other_domain = JZTranscript::new(b"jzexe.transcript.other");
+        other_domain.append_message(b"msg", b"hello");
+        assert_ne!(c, other_domain.challenge_scalar::<F>(b"c"));
+
+        let mut other_label = JZTranscript::new(b"jzexe.transcript.test");
+        other_label.append_message(b"msh", b"hello");
+        assert_ne!(c, other_label.challenge_scalar::<F>(b"c"));
+
+        let mut split = JZTranscript::new(b"jzexe.transcript.test");
+        split.append_message(b"msg", b"hel");
+        split.append_message(b"", b"lo");
+        assert_ne!(c, split.challenge_scalar::<F>(b"c"));
+    }
+}
@@ -0,0 +1,149 @@
+use ark_crypto_primitives::sponge::{
+    Absorb,
+    constraints::CryptographicSpongeVar,
+    poseidon::constraints::PoseidonSpongeVar,
+};
+use ark_ff::PrimeField;
+use ark_r1cs_std::prelude::*;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::ToConstraintFieldGadget;
+use ark_relations::r1cs::*;
+
+use crate::prf::poseidon::{pack_bytes, constraints::pack_byte_vars};
+use crate::utils;
+
+use super::{RATE, MESSAGE_TAG, ELEMENTS_TAG};
+
+/// in-circuit counterpart of JZPoseidonTranscript; labels and the domain
+/// are public, so they are absorbed as constants
+#[derive(Clone)]
+pub struct JZPoseidonTranscriptVar<F: PrimeField + Absorb> {
+    sponge: PoseidonSpongeVar<F>,
+}
+
+impl<F: PrimeField + Absorb> JZPoseidonTranscriptVar<F> {
+    pub fn new(cs: ConstraintSystemRef<F>, domain: &[u8]) -> Result<Self> {
+        let params = utils::poseidon_parameters::<F>(RATE);
+        let mut transcript = JZPoseidonTranscriptVar { sponge: PoseidonSpongeVar::new(cs, &params) };
+        // the domain is public, so it is absorbed as constants
+        transcript.append_label(b"dom-sep")?;
+        transcript.sponge.absorb(&FpVar::constant(F::from(MESSAGE_TAG)))?;
+        transcript.append_label(domain)?;
+        Ok(transcript)
+    }
+
+    fn append_label(&mut self, label: &[u8]) -> Result<()> {
+        let elements: Vec<FpVar<F>> = pack_bytes::<F>(label)
+            .into_iter()
+            .map(FpVar::constant)
+            .collect();
+        self.sponge.absorb(&elements)
+    }
+
+    /// ELEMENTS_TAG || count || elements; the count is fixed by the
+    /// circuit's shape, so the prefix is constant
+    fn append_elements(&mut self, elements: &[FpVar<F>]) -> Result<()> {
+        self.sponge.absorb(&vec![
+            FpVar::constant(F::from(ELEMENTS_TAG)),
+            FpVar::constant(F::from(elements.len() as u64)),
+        ])?;
+        self.sponge.absorb(&elements.to_vec())
+    }
+
+    pub fn append_message(&mut self, label: &[u8], message: &[UInt8<F>]) -> Result<()> {
+        self.append_label(label)?;
+        self.sponge.absorb(&FpVar::constant(F::from(MESSAGE_TAG)))?;
+        self.sponge.absorb(&pack_byte_vars(message)?)
+    }
+
+    pub fn append_scalar(&mut self, label: &[u8], scalar: &FpVar<F>) -> Result<()> {
+        self.append_label(label)?;
+        self.append_elements(std::slice::from_ref(scalar))
+    }
+
+    pub fn append_point<G: ToConstraintFieldGadget<F>>(&mut self, label: &[u8], point: &G) -> Result<()> {
+        self.append_label(label)?;
+        self.append_elements(&point.to_constraint_field()?)
+    }
+
+    pub fn challenge_scalar(&mut self, label: &[u8]) -> Result<FpVar<F>> {
+        self.append_label(label)?;
+        Ok(self.sponge.squeeze_field_elements(1)?.remove(0))
+    }
+
+    pub fn challenge_bits(&mut self, label: &[u8], num_bits: usize) -> Result<Vec<Boolean<F>>> {
+        self.append_label(label)?;
+        self.sponge.squeeze_bits(num_bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::poseidon::JZPoseidonTranscript;
+    use ark_ec::CurveGroup;
+    use ark_ff::ToConstraintField;
+    use ark_std::{test_rng, UniformRand};
+
+    /// runs the same transcript natively and in-circuit over F, with one
+    /// point of a curve whose coordinates are in F
+    fn check_transcript<F, G, GV>(point: G)
+        where   F: PrimeField + Absorb,
+                G: ToConstraintField<F>,
+                GV: ToConstraintFieldGadget<F> + AllocVar<G, F>,
+    {
+        let mut rng = test_rng();
+        let scalar = F::rand(&mut rng);
+        let message = b"jzexe transcript message, longer than one packed chunk".to_vec();
+
+        let mut native = JZPoseidonTranscript::<F>::new(b"jzexe.transcript.poseidon.test");
+        native.append_message(b"message", &message);
+        native.append_scalar(b"scalar", &scalar);
+        native.append_point(b"point", &point);
+        let c = native.challenge_scalar(b"c");
+        let bits = native.challenge_bits(b"bits", 128);
+
+        let cs = ConstraintSystem::<F>::new_ref();
+        let message_var = UInt8::new_witness_vec(cs.clone(), &message).unwrap();
+        let scalar_var = FpVar::new_witness(cs.clone(), || Ok(scalar)).unwrap();
+        let point_var = GV::new_witness(cs.clone(), || Ok(&point)).unwrap();
+
+        let mut transcript_var = JZPoseidonTranscriptVar::<F>::new(cs.clone(), b"jzexe.transcript.poseidon.test").unwrap();
+        transcript_var.append_message(b"message", &message_var).unwrap();
+        transcript_var.append_scalar(b"scalar", &scalar_var).unwrap();
+        transcript_var.append_point(b"point", &point_var).unwrap();
+        let c_var = transcript_var.challenge_scalar(b"c").unwrap();
+        let bits_var = transcript_var.challenge_bits(b"bits", 128).unwrap();
+
+        assert_eq!(c_var.value().unwrap(), c);
+        assert_eq!(bits_var.value().unwrap(), bits);
+        assert!(cs.is_satisfied().unwrap());
+
+        // the domain separates transcripts
+        let mut other = JZPoseidonTranscript::<F>::new(b"jzexe.transcript.poseidon.other");
+        other.append_message(b"message", &message);
+        other.append_scalar(b"scalar", &scalar);
+        other.append_point(b"point", &point);
+        assert_ne!(other.challenge_scalar(b"c"), c);
+
+        // a zero scalar is not the empty message under the same label
+        let mut zero = JZPoseidonTranscript::<F>::new(b"jzexe.transcript.poseidon.test");
+        zero.append_scalar(b"value", &F::zero());
+        let mut empty = JZPoseidonTranscript::<F>::new(b"jzexe.transcript.poseidon.test");
+        empty.append_message(b"value", b"");
+        assert_ne!(zero.challenge_scalar(b"c"), empty.challenge_scalar(b"c"));
+    }
+
+    #[test]
+    fn test_poseidon_transcript_bls12_377() {
+        let point = ark_ed_on_bls12_377::EdwardsAffine::rand(&mut test_rng());
+        check_transcript::<ark_bls12_377::Fr, _, ark_ed_on_bls12_377::constraints::EdwardsVar>(point);
+    }
+
+    #[test]
+    fn test_poseidon_transcript_bw6_761() {
+        // a KZG commitment, whose coordinates are native to BW6-761
+        let point = ark_bls12_377::G1Projective::rand(&mut test_rng()).into_affine();
+        check_transcript::<ark_bw6_761::Fr, _, ark_bls12_377::constraints::G1Var>(point);
+    }
+}
@@ -0,0 +1,79 @@
+pub mod constraints;
+
+use ark_crypto_primitives::sponge::{
+    Absorb,
+    CryptographicSponge,
+    poseidon::{PoseidonConfig, PoseidonSponge},
+};
+use ark_ff::{PrimeField, ToConstraintField};
+
+use crate::prf::poseidon::pack_bytes;
+use crate::utils;
+
+/// rate of the transcript's Poseidon sponge, as for the PRF and the VRF
+pub const RATE: usize = 2;
+
+/// absorbed in front of every value, so a byte message and a run of field
+/// elements never share an encoding
+pub const MESSAGE_TAG: u64 = 0;
+pub const ELEMENTS_TAG: u64 = 1;
+
+/// Fiat–Shamir transcript over a Poseidon sponge on the circuit's field,
+/// with the same labelling as JZTranscript. Labels and byte messages are
+/// absorbed as pack_bytes elements, which carry their byte length;
+/// scalars and points as the field elements they consist of, after their
+/// count. Every value is prefixed with its type tag. Challenges
+/// are squeezed from the running sponge, so they depend on every earlier
+/// challenge. JZPoseidonTranscriptVar computes the same challenges in R1CS.
+#[derive(Clone)]
+pub struct JZPoseidonTranscript<F: PrimeField + Absorb> {
+    sponge: PoseidonSponge<F>,
+}
+
+impl<F: PrimeField + Absorb> JZPoseidonTranscript<F> {
+    pub fn new(domain: &[u8]) -> Self {
+        let params: PoseidonConfig<F> = utils::poseidon_parameters::<F>(RATE);
+        let mut transcript = JZPoseidonTranscript { sponge: PoseidonSponge::new(&params) };
+        transcript.append_message(b"dom-sep", domain);
+        transcript
+    }
+
+    fn append_label(&mut self, label: &[u8]) {
+        self.sponge.absorb(&pack_bytes::<F>(label));
+    }
+
+    /// ELEMENTS_TAG || count || elements
+    fn append_elements(&mut self, elements: &[F]) {
+        self.sponge.absorb(&vec![F::from(ELEMENTS_TAG), F::from(elements.len() as u64)]);
+        self.sponge.absorb(&elements.to_vec());
+    }
+
+    pub fn append_message(&mut self, label: &[u8], message: &[u8]) {
+        self.append_label(label);
+        self.sponge.absorb(&F::from(MESSAGE_TAG));
+        self.sponge.absorb(&pack_bytes::<F>(message));
+    }
+
+    pub fn append_scalar(&mut self, label: &[u8], scalar: &F) {
+        self.append_label(label);
+        self.append_elements(&[*scalar]);
+    }
+
+    /// any point with coordinates in F, e.g. an Edwards point over the
+    /// embedded curve or a BLS12-377 G1 point in a BW6-761 circuit
+    pub fn append_point<G: ToConstraintField<F>>(&mut self, label: &[u8], point: &G) {
+        self.append_label(label);
+        self.append_elements(&point.to_field_elements().unwrap());
+    }
+
+    pub fn challenge_scalar(&mut self, label: &[u8]) -> F {
+        self.append_label(label);
+        self.sponge.squeeze_field_elements::<F>(1)[0]
+    }
+
+    /// short challenges, e.g. 128 bits for cheap in-circuit scalar muls
+    pub fn challenge_bits(&mut self, label: &[u8], num_bits: usize) -> Vec<bool> {
+        self.append_label(label);
+        self.sponge.squeeze_bits(num_bits)
+    }
+}
@@ -2,10 +2,11 @@ use ark_ff::*;
 use ark_std::*;
 use ark_ff::{
     PrimeField,
-    BigInt,
     field_hashers::{DefaultFieldHasher, HashToField},
 };
 use ark_crypto_primitives::crh::sha256::Sha256;
+use ark_crypto_primitives::sponge::poseidon::{PoseidonConfig, find_poseidon_ark_and_mds};
+use ark_ec::{AffineRepr, twisted_edwards::{self, TECurveConfig}};
 use ark_poly::{
     Polynomial,
     univariate::DensePolynomial, 
@@ -14,12 +15,6 @@ use ark_poly::{
     Evaluations
 };
 
-pub fn bytes_to_field<F, const N: usize>(bytes: &[u8]) -> F 
-    where F: PrimeField + From<BigInt<N>>
-{
-    F::from(BigInt::<N>::from_bits_le(bytes_to_bits(bytes).as_slice()))
-}
-
 pub fn bytes_to_bits(bytes: &[u8]) -> Vec<bool> {
     let mut bits = Vec::with_capacity(bytes.len() * 8);
     for byte in bytes {
@@ -87,14 +82,73 @@ pub fn poly_eval_mult_const<F: FftField + PrimeField>(
     new_poly
 }
 
-pub fn fs_hash<F: FftField + PrimeField>(
-    x: &[Vec<u8>], 
-    num_output: usize
-) -> Vec<F> {
-    let hasher = <DefaultFieldHasher<Sha256> as HashToField<F>>::new(b"jigzexe");
-    let field_elements = hasher.hash_to_field(&x.concat(), num_output);
+/// nothing-up-my-sleeve point in the prime-order subgroup of a twisted
+/// Edwards curve: try-and-increment on y = H(domain, index || counter),
+/// followed by cofactor clearing, so nobody knows its discrete log
+/// with respect to any other point derived this way
+pub fn hash_to_curve<P: TECurveConfig>(
+    domain: &[u8],
+    index: u64
+) -> twisted_edwards::Projective<P> {
+    hash_bytes_to_curve::<P>(domain, &index.to_le_bytes())
+}
+
+/// hash_to_curve on an arbitrary message, e.g. a VRF input
+pub fn hash_bytes_to_curve<P: TECurveConfig>(
+    domain: &[u8],
+    message: &[u8]
+) -> twisted_edwards::Projective<P> {
+    let hasher = <DefaultFieldHasher<Sha256> as HashToField<P::BaseField>>::new(domain);
+
+    let mut counter = 0u64;
+    loop {
+        let mut msg = message.to_vec();
+        msg.extend_from_slice(&counter.to_le_bytes());
+        let y: P::BaseField = hasher.hash_to_field(&msg, 1)[0];
+
+        if let Some(point) = twisted_edwards::Affine::<P>::get_point_from_y_unchecked(y, false) {
+            let point = point.clear_cofactor();
+            if !point.is_zero() {
+                return point.into_group();
+            }
+        }
+        counter += 1;
+    }
+}
+
+/// Poseidon parameters with capacity 1 and the given rate. The S-box
+/// x^alpha must be a permutation, and alpha = 17 is coprime to p - 1 for
+/// both BLS12-377 Fr and BW6-761 Fr (BLS12-377 Fq). The round numbers are
+/// those of calc_round_numbers.py, the reference script of the Poseidon
+/// paper (Grassi et al., USENIX Security 2021; see
+/// https://extgit.iaik.tugraz.at/krypto/hadeshash), for 128-bit security
+/// with its margin of 2 more full and 7.5% more partial rounds: it gives
+/// R_F = 8, R_P = 31 for alpha = 17 at widths 3 to 5 over both fields, so
+/// rates 2 to 4 are covered. Round constants and the MDS matrix come from
+/// the paper's Grain LFSR, seeded with the field size
+pub fn poseidon_parameters<F: PrimeField>(rate: usize) -> PoseidonConfig<F> {
+    assert!((2..=4).contains(&rate), "no Poseidon round numbers for rate {}", rate);
+    let full_rounds = 8;
+    let partial_rounds = 31;
+    let alpha = 17;
+
+    let (ark, mds) = find_poseidon_ark_and_mds::<F>(
+        F::MODULUS_BIT_SIZE as u64,
+        rate,
+        full_rounds,
+        partial_rounds,
+        0
+    );
 
-    field_elements
+    PoseidonConfig::new(
+        full_rounds as usize,
+        partial_rounds as usize,
+        alpha,
+        mds,
+        ark,
+        rate,
+        1
+    )
 }
 
 // returns t(X) = c
@@ -162,6 +162,18 @@ mod tests {
     type MTEdOnBw6_761 = config::ed_on_bw6_761::MerkleTreeParams;
     type MTVarEdOnBw6_761 = config::ed_on_bw6_761::MerkleTreeParamsVar;
 
+    type MTChunked = config::ed_on_bw6_761::ChunkedMerkleTreeParams;
+    type MTVarChunked = config::ed_on_bw6_761::ChunkedMerkleTreeParamsVar;
+
+    type MTChunkedEdOnBls12_377 = config::ed_on_bls12_377::ChunkedMerkleTreeParams;
+    type MTVarChunkedEdOnBls12_377 = config::ed_on_bls12_377::ChunkedMerkleTreeParamsVar;
+
+    type MTBhEdOnBls12_377 = config::ed_on_bls12_377::BhMerkleTreeParams;
+    type MTVarBhEdOnBls12_377 = config::ed_on_bls12_377::BhMerkleTreeParamsVar;
+
+    type MTBhEdOnBw6_761 = config::ed_on_bw6_761::BhMerkleTreeParams;
+    type MTVarBhEdOnBw6_761 = config::ed_on_bw6_761::BhMerkleTreeParamsVar;
+
     fn generate_vc_params<P: crate::merkle_tree::Config>() -> JZVectorCommitmentParams<P> {
         let seed = [0u8; 32];
         let mut rng = rand_chacha::ChaCha8Rng::from_seed(seed);
@@ -264,4 +276,127 @@ mod tests {
         );
         assert!(cs.is_satisfied().unwrap(), "constraints not satisfied");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_vector_storage_chunked_leaf_constraint_gen() {
+        // a G1 point plus 64 bytes of metadata does not fit one Pedersen window
+        let mut records = Vec::new();
+        for x in 0..4u8 {
+            let g_pow_x_i = ark_bls12_377::G1Affine::generator()
+                .mul_bigint(BigInteger256::from(x))
+                .into_affine();
+            records.push((g_pow_x_i, [x; 64]));
+        }
+
+        let idx = 2;
+        let db = JZVectorDB::<MTChunked, (ark_bls12_377::G1Affine, [u8; 64])>::new(
+            generate_vc_params::<MTChunked>(), &records
+        );
+        let root = db.commitment();
+        let path = db.proof(idx);
+        let proof = JZVectorCommitmentOpeningProof::<MTChunked, (ark_bls12_377::G1Affine, [u8; 64])> {
+            root,
+            record: records[idx],
+            path: path.clone(),
+        };
+
+        let vc_params = generate_vc_params::<MTChunked>();
+        assert!(verify_proof(&vc_params, &root, &records[idx], &path));
+
+        let cs = ConstraintSystem::<ark_bw6_761::Fr>::new_ref();
+
+        let params_var = JZVectorCommitmentParamsVar::
+        <ark_bw6_761::Fr, MTChunked, MTVarChunked>
+        ::new_constant(
+            cs.clone(),
+            &vc_params
+        ).unwrap();
+
+        let proof_var = JZVectorCommitmentOpeningProofVar::
+        <ark_bw6_761::Fr, MTChunked, MTVarChunked>
+        ::new_witness(
+            cs.clone(),
+            || Ok(&proof)
+        ).unwrap();
+
+        generate_constraints(
+            cs.clone(),
+            &params_var,
+            &proof_var,
+        );
+        assert!(cs.is_satisfied().unwrap(), "constraints not satisfied");
+
+        // the same leaves under the BLS12-377 scalar field
+        membership_constraints::<ark_bls12_377::Fr, MTChunkedEdOnBls12_377, MTVarChunkedEdOnBls12_377, _>(
+            generate_vc_params(), &records, idx
+        );
+    }
+
+    // opens records[idx] in-circuit and returns the number of constraints
+    fn membership_constraints<ConstraintF, P, PG, L>(
+        vc_params: JZVectorCommitmentParams<P>,
+        records: &[L],
+        idx: usize
+    ) -> usize
+        where   ConstraintF: Field,
+                L: CanonicalSerialize + Clone + Sized,
+                [u8]: std::borrow::Borrow<<P as Config>::Leaf>,
+                P: Config<Leaf = [u8]>,
+                PG: ConfigGadget<P, ConstraintF, Leaf = [UInt8<ConstraintF>]>
+    {
+        let db = JZVectorDB::<P, L>::new(vc_params, records);
+        let vc_params = &db.vc_params;
+        let proof = JZVectorCommitmentOpeningProof::<P, L> {
+            root: db.commitment(),
+            record: records[idx].clone(),
+            path: db.proof(idx),
+        };
+        assert!(verify_proof(vc_params, &proof.root, &proof.record, &proof.path));
+
+        let cs = ConstraintSystem::<ConstraintF>::new_ref();
+        let params_var = JZVectorCommitmentParamsVar::<ConstraintF, P, PG>
+            ::new_constant(cs.clone(), vc_params).unwrap();
+        let proof_var = JZVectorCommitmentOpeningProofVar::<ConstraintF, P, PG>
+            ::new_witness(cs.clone(), || Ok(&proof)).unwrap();
+
+        generate_constraints(cs.clone(), &params_var, &proof_var);
+        assert!(cs.is_satisfied().unwrap(), "constraints not satisfied");
+        cs.num_constraints()
+    }
+
+    #[test]
+    fn test_vector_storage_bowe_hopwood_constraint_gen() {
+        let bigints: Vec<BigInteger256> = (0..16u8).map(BigInteger256::from).collect();
+
+        let window4 = membership_constraints::
+            <ark_bls12_377::Fr, MTEdOnBls12_377, MTVarEdOnBls12_377, _>(generate_vc_params(), &bigints, 3);
+        let bowe_hopwood = membership_constraints::
+            <ark_bls12_377::Fr, MTBhEdOnBls12_377, MTVarBhEdOnBls12_377, _>(generate_vc_params(), &bigints, 3);
+        assert_eq!((window4, bowe_hopwood), (27909, 8685));
+
+        let points: Vec<ark_bls12_377::G1Affine> = (0..16u8)
+            .map(|x| ark_bls12_377::G1Affine::generator()
+                .mul_bigint(BigInteger256::from(x))
+                .into_affine())
+            .collect();
+
+        let window4 = membership_constraints::
+            <ark_bw6_761::Fr, MTEdOnBw6_761, MTVarEdOnBw6_761, _>(generate_vc_params(), &points, 5);
+        let bowe_hopwood = membership_constraints::
+            <ark_bw6_761::Fr, MTBhEdOnBw6_761, MTVarBhEdOnBw6_761, _>(generate_vc_params(), &points, 5);
+        assert_eq!((window4, bowe_hopwood), (42922, 13960));
+    }
+
+    #[test]
+    fn test_vector_storage_from_domain_constraint_gen() {
+        let bigints: Vec<BigInteger256> = (0..16u8).map(BigInteger256::from).collect();
+
+        // hashed-to-curve generators have the structure the gadgets expect
+        membership_constraints::<ark_bls12_377::Fr, MTEdOnBls12_377, MTVarEdOnBls12_377, _>(
+            JZVectorCommitmentParams::from_domain(DEFAULT_DOMAIN), &bigints, 7
+        );
+        membership_constraints::<ark_bls12_377::Fr, MTBhEdOnBls12_377, MTVarBhEdOnBls12_377, _>(
+            JZVectorCommitmentParams::from_domain(DEFAULT_DOMAIN), &bigints, 7
+        );
+    }
+}
@@ -11,6 +11,10 @@ use ark_std::rand::Rng;
 use ark_serialize::CanonicalSerialize;
 use ark_std::borrow::*;
 
+use crate::crh::JZDomainSetup;
+
+pub const DEFAULT_DOMAIN: &str = "jzexe.vector_commitment";
+
 pub struct JZVectorCommitmentParams<P: Config> 
 {
     pub leaf_crh_params: <P::LeafHash as CRHScheme>::Parameters,
@@ -31,6 +35,20 @@ impl<P: Config> JZVectorCommitmentParams<P> {
     }
 }
 
+impl<P: Config> JZVectorCommitmentParams<P>
+    where   P::LeafHash: JZDomainSetup<Parameters = <P::LeafHash as CRHScheme>::Parameters>,
+            P::TwoToOneHash: JZDomainSetup<Parameters = <P::TwoToOneHash as TwoToOneCRHScheme>::Parameters>,
+{
+    /// derives the leaf and inner node generators by hashing to the curve
+    /// under two subdomains, so the tree needs no trusted party
+    pub fn from_domain(domain: &str) -> Self {
+        JZVectorCommitmentParams {
+            leaf_crh_params: P::LeafHash::setup_from_domain(&format!("{}.leaf", domain)),
+            two_to_one_params: P::TwoToOneHash::setup_from_domain(&format!("{}.two_to_one", domain)),
+        }
+    }
+}
+
 pub type JZVectorCommitment<P> = <P as Config>::InnerDigest;
 pub type JZVectorCommitmentPath<P> = Path<P>;
 pub type JZVectorCommitmentLeafDigest<P> = <P as Config>::LeafDigest;
@@ -220,4 +238,21 @@ mod tests {
         let proof = db.proof(some_index);
         assert!(verify_proof(&generate_vc_params::<MT>(), &com, &records[some_index], &proof));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_vector_storage_from_domain() {
+        let records: Vec<BigInteger256> = (0..16u8).map(BigInteger256::from).collect();
+
+        let db = JZVectorDB::<MT, BigInteger256>::new(
+            JZVectorCommitmentParams::from_domain(DEFAULT_DOMAIN), &records
+        );
+        let com = db.commitment();
+        let proof = db.proof(3);
+
+        // anyone can rederive the parameters, and they are bound to the domain
+        let vc_params = JZVectorCommitmentParams::<MT>::from_domain(DEFAULT_DOMAIN);
+        assert!(verify_proof(&vc_params, &com, &records[3], &proof));
+        let other_params = JZVectorCommitmentParams::<MT>::from_domain("jzexe.vector_commitment.other");
+        assert!(!verify_proof(&other_params, &com, &records[3], &proof));
+    }
+}
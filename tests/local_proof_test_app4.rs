@@ -57,7 +57,7 @@ fn setup_witness() -> PRFCircuit {
     // ];
 
     PRFCircuit {
-        prf_instance: JZPRFInstance::new(&params, &input, &key),
+        prf_instance: JZPRFInstance::new(&params, JZPRFDomain::Address, &input, &key),
     }
 }
 
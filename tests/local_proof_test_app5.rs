@@ -193,7 +193,7 @@ fn setup_witness() -> SpendCircuit {
 
     SpendCircuit {
         prf_instance: JZPRFInstance::new(
-            &prf_params, &[0u8; 32], &alice_key().0
+            &prf_params, JZPRFDomain::Address, &[0u8; 32], &alice_key().0
         ),
         record: coins[0].clone(),
         db: db,
@@ -205,10 +205,10 @@ fn alice_key() -> ([u8; 32], [u8; 31]) {
     let privkey = [20u8; 32];
     let pubkey =
     [
-        218, 61, 173, 102, 17, 186, 176, 174, 
-        54, 64, 4, 87, 114, 16, 209, 133, 
-        153, 47, 114, 88, 54, 48, 138, 7,
-        136, 114, 216, 152, 205, 164, 171
+        216, 194, 114, 56, 56, 148, 162, 168,
+        126, 208, 197, 4, 206, 88, 52, 223,
+        161, 191, 172, 140, 162, 88, 6, 87,
+        134, 68, 121, 10, 17, 20, 77
     ];
 
     (privkey, pubkey)
@@ -218,10 +218,10 @@ fn bob_key() -> ([u8; 32], [u8; 31]) {
     let privkey = [25u8; 32];
     let pubkey =
     [
-        217, 214, 252, 243, 200, 147, 117, 28, 
-        142, 219, 58, 120, 65, 180, 251, 74, 
-        234, 28, 72, 194, 161, 148, 52, 219, 
-        10, 34, 21, 17, 33, 38, 77,
+        124, 247, 28, 163, 126, 209, 16, 120,
+        30, 238, 173, 116, 215, 197, 19, 57,
+        166, 125, 138, 100, 163, 55, 191, 202,
+        18, 93, 226, 17, 42, 80, 151,
     ];
 
     (privkey, pubkey)
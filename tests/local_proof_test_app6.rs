@@ -9,7 +9,6 @@ use ark_relations::r1cs::*;
 use ark_groth16::{Groth16, ProvingKey, VerifyingKey};
 use ark_snark::SNARK;
 
-use lib_sanctum::utils;
 use lib_sanctum::{vector_commitment, record_commitment, prf};
 use lib_sanctum::vector_commitment::bytes::pedersen::{*, constraints::*};
 use lib_sanctum::record_commitment::kzg::{*, constraints::*};
@@ -136,15 +135,9 @@ impl ConstraintSynthesizer<ConstraintF> for SpendCircuit {
 
         // --------------- Nullifier ------------------
 
-        let nullifier_prf_f = BigInt::<6>::from_bits_le(
-            &utils::bytes_to_bits(
-                &self.prf_instance_nullifier.evaluate()
-            )
-        );
-
-        let nullifier_x_var = ark_bls12_377::constraints::FqVar::new_input(
+        let nullifier_var = ark_bls12_377::constraints::FqVar::new_input(
             ark_relations::ns!(cs, "nullifier_prf"), 
-            || { Ok(ark_bls12_377::Fq::from(nullifier_prf_f)) },
+            || { Ok(self.prf_instance_nullifier.evaluate_field::<ConstraintF>()) },
         ).unwrap();
 
         let params_var = JZPRFParamsVar::<H, HG, ConstraintF>::new_constant(
@@ -157,9 +150,9 @@ impl ConstraintSynthesizer<ConstraintF> for SpendCircuit {
             || Ok(self.prf_instance_nullifier)
         ).unwrap();
 
-        prf::constraints::generate_constraints(
+        let nullifier_prf_var = prf::constraints::generate_field_constraints(
             cs.clone(), &params_var, &nullifier_prf_instance_var
-        );
+        )?;
 
         //--------------- Binding the four ------------------
 
@@ -180,12 +173,7 @@ impl ConstraintSynthesizer<ConstraintF> for SpendCircuit {
         }
 
         // prove PRF output of nullifier
-        let mut prf_byte_vars: Vec::<UInt8<ConstraintF>> = Vec::new();
-        prf_byte_vars.extend_from_slice(&nullifier_x_var.to_bytes()?);
-
-        for i in 0..std::cmp::min(nullifier_prf_instance_var.output_var.len(), prf_byte_vars.len()) {
-            nullifier_prf_instance_var.output_var[i].enforce_equal(&prf_byte_vars[i])?;
-        }
+        nullifier_prf_var.enforce_equal(&nullifier_var)?;
 
         Ok(())
     }
@@ -232,10 +220,10 @@ fn setup_witness() -> SpendCircuit {
 
     SpendCircuit {
         prf_instance_ownership: JZPRFInstance::new(
-            &prf_params, &[0u8; 32], &alice_key().0
+            &prf_params, JZPRFDomain::Address, &[0u8; 32], &alice_key().0
         ),
         prf_instance_nullifier: JZPRFInstance::new(
-            &prf_params, coins[0].fields[RHO].as_slice(), &alice_key().0
+            &prf_params, JZPRFDomain::Nullifier, coins[0].fields[RHO].as_slice(), &alice_key().0
         ),
         record: coins[0].clone(),
         db: db,
@@ -247,10 +235,10 @@ fn alice_key() -> ([u8; 32], [u8; 31]) {
     let privkey = [20u8; 32];
     let pubkey =
     [
-        218, 61, 173, 102, 17, 186, 176, 174, 
-        54, 64, 4, 87, 114, 16, 209, 133, 
-        153, 47, 114, 88, 54, 48, 138, 7,
-        136, 114, 216, 152, 205, 164, 171
+        216, 194, 114, 56, 56, 148, 162, 168,
+        126, 208, 197, 4, 206, 88, 52, 223,
+        161, 191, 172, 140, 162, 88, 6, 87,
+        134, 68, 121, 10, 17, 20, 77
     ];
 
     (privkey, pubkey)
@@ -260,10 +248,10 @@ fn bob_key() -> ([u8; 32], [u8; 31]) {
     let privkey = [25u8; 32];
     let pubkey =
     [
-        217, 214, 252, 243, 200, 147, 117, 28, 
-        142, 219, 58, 120, 65, 180, 251, 74, 
-        234, 28, 72, 194, 161, 148, 52, 219, 
-        10, 34, 21, 17, 33, 38, 77,
+        124, 247, 28, 163, 126, 209, 16, 120,
+        30, 238, 173, 116, 215, 197, 19, 57,
+        166, 125, 138, 100, 163, 55, 191, 202,
+        18, 93, 226, 17, 42, 80, 151,
     ];
 
     (privkey, pubkey)
@@ -294,13 +282,7 @@ fn spending() {
 
     let blinded_com = circuit.record.blinded_commitment().into_affine();
     let input_root = circuit.db.commitment();
-    let nullifier = ConstraintF::from(
-            BigInt::<6>::from_bits_le(
-            &utils::bytes_to_bits(
-                &circuit.prf_instance_nullifier.evaluate()
-            )
-        )
-    );
+    let nullifier = circuit.prf_instance_nullifier.evaluate_field::<ConstraintF>();
 
     let public_input = vec![ 
         blinded_com.x,